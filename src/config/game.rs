@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default)]
@@ -10,6 +11,8 @@ pub struct GameConfig {
 	pub window_height: Option<u32>,
 	pub jvm_args: Option<String>,
 	pub game_args: Option<String>,
+	/// 绑定的账户 ID，未设置时使用当前选中的账户
+	pub account: Option<Uuid>,
 }
 
 impl GameConfig {
@@ -27,6 +30,7 @@ impl GameConfig {
 				.clone()
 				.unwrap_or_else(|| defaults.jvm_args.clone()),
 			game_args: self.game_args.clone().unwrap_or_default(),
+			account: self.account,
 		}
 	}
 }
@@ -39,4 +43,5 @@ pub struct ResolvedGameConfig {
 	pub window_height: u32,
 	pub jvm_args: String,
 	pub game_args: String,
+	pub account: Option<Uuid>,
 }
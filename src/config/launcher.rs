@@ -7,9 +7,27 @@ pub struct LauncherConfig {
 	pub theme: String,
 	pub language: String,
 	pub cluster_path: Option<PathBuf>,
+	/// 同时扫描实例的其他游戏目录
+	pub extra_clusters: Vec<PathBuf>,
 	pub window_width: u32,
 	pub window_height: u32,
 	pub download_concurrency: u8,
+	/// Adoptium API 地址，可替换为镜像
+	pub adoptium_api: String,
+	/// 库自带的仓库下载失败时依次尝试的 Maven 仓库
+	pub maven_repositories: Vec<String>,
+	/// Fabric meta API 地址，可替换为镜像
+	pub fabric_meta: String,
+	/// Quilt meta API 地址
+	pub quilt_meta: String,
+	/// Forge 安装器所在的 Maven 仓库
+	pub forge_maven: String,
+	/// NeoForge 所在的 Maven 仓库
+	pub neoforge_maven: String,
+	/// OptiFine 下载源，需提供 BMCLAPI 格式的 `/optifine` 接口
+	pub optifine_source: String,
+	/// 微软登录使用的 Azure 应用客户端 ID
+	pub microsoft_client_id: String,
 	pub game: GameDefaults,
 }
 
@@ -29,9 +47,21 @@ impl Default for LauncherConfig {
 			theme: "dark".into(),
 			language: "zh-CN".into(),
 			cluster_path: None,
+			extra_clusters: Vec::new(),
 			window_width: 900,
 			window_height: 550,
 			download_concurrency: 5,
+			adoptium_api: "https://api.adoptium.net".into(),
+			maven_repositories: vec![
+				"https://libraries.minecraft.net/".into(),
+				"https://repo1.maven.org/maven2/".into(),
+			],
+			fabric_meta: "https://meta.fabricmc.net".into(),
+			quilt_meta: "https://meta.quiltmc.org".into(),
+			forge_maven: "https://maven.minecraftforge.net/".into(),
+			neoforge_maven: "https://maven.neoforged.net/releases/".into(),
+			optifine_source: "https://bmclapi2.bangbang93.com".into(),
+			microsoft_client_id: String::new(),
 			game: GameDefaults::default(),
 		}
 	}
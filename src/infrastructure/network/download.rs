@@ -6,6 +6,7 @@ use std::time::Duration;
 use futures_util::TryStreamExt;
 use reqwest::header::RANGE;
 use reqwest::{Client, StatusCode};
+use sha1::Sha1;
 use sha2::{Digest, Sha256};
 use thiserror::Error;
 use tokio::fs::{self, OpenOptions};
@@ -20,6 +21,7 @@ const PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
 
 #[derive(Clone, Debug)]
 pub enum Checksum {
+	Sha1(String),
 	Sha256(String),
 }
 
@@ -320,20 +322,18 @@ async fn file_matches_checksum(path: &Path, checksum: &Checksum) -> Result<bool,
 		return Ok(false);
 	}
 
-	match checksum {
-		Checksum::Sha256(expected) => {
-			let path = path.to_owned();
-			let expected = expected.clone();
-			let digest = tokio::task::spawn_blocking(move || -> Result<String, std::io::Error> {
-				let data = std::fs::read(&path)?;
-				let hash = hex::encode(Sha256::digest(data));
-				Ok(hash)
-			})
-			.await
-			.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))??;
-			Ok(digest.eq_ignore_ascii_case(&expected))
-		}
-	}
+	let path = path.to_owned();
+	let checksum = checksum.clone();
+	tokio::task::spawn_blocking(move || -> Result<bool, std::io::Error> {
+		let data = std::fs::read(&path)?;
+		let (digest, expected) = match &checksum {
+			Checksum::Sha1(expected) => (hex::encode(Sha1::digest(&data)), expected),
+			Checksum::Sha256(expected) => (hex::encode(Sha256::digest(&data)), expected),
+		};
+		Ok(digest.eq_ignore_ascii_case(expected))
+	})
+	.await
+	.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?
 }
 
 #[cfg(test)]
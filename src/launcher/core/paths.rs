@@ -7,6 +7,13 @@ pub fn config_dir() -> Result<PathBuf> {
 		.map(|p| p.join("hako"))
 }
 
+/// 启动器自行管理的数据（运行时、工具 jar 等）
+pub fn data_dir() -> Result<PathBuf> {
+	dirs::data_dir()
+		.context("Failed to get data directory")
+		.map(|p| p.join("hako"))
+}
+
 pub fn cache_dir() -> Result<PathBuf> {
 	let cache = std::env::temp_dir().join("hako_cache");
 	if !cache.exists() {
@@ -1,6 +1,7 @@
 use crate::minecraft::account::AccountManager;
 use crate::config::manager::ConfigManager;
-use crate::minecraft::game::instance::GameInstance;
+use crate::minecraft::game::instance::{GameInstance, InstanceScanner};
+use crate::minecraft::game::java::JavaInventory;
 use crate::minecraft::tasks::download::{DownloadProgressState, ProgressRef};
 use crate::launcher::task::handle::TaskId;
 use crate::launcher::task::manager::TaskManager;
@@ -13,6 +14,7 @@ static APP_STATE: OnceLock<AppState> = OnceLock::new();
 pub struct AppState {
 	pub config: ConfigManager,
 	pub accounts: AccountManager,
+	pub java: JavaInventory,
 	pub task_manager: Arc<TaskManager>,
 	pub instances: RwLock<Vec<GameInstance>>,
 	pub current_instance: Mutex<Option<usize>>,
@@ -21,11 +23,23 @@ pub struct AppState {
 
 impl AppState {
 	pub fn init() -> &'static Self {
-		APP_STATE.get_or_init(|| {
+		let mut created = false;
+		let state = APP_STATE.get_or_init(|| {
+			created = true;
 			let state = Self::create();
 			state.scan_instances();
 			state
-		})
+		});
+		if created {
+			crate::minecraft::tasks::account::spawn_background_refresh();
+			// 首次运行没有缓存，后台扫描一次
+			if state.java.is_empty() {
+				tokio::task::spawn_blocking(|| {
+					AppState::get().java.refresh();
+				});
+			}
+		}
+		state
 	}
 
 	pub fn get() -> &'static Self {
@@ -35,7 +49,8 @@ impl AppState {
 	fn create() -> Self {
 		Self {
 			config: ConfigManager::default(),
-			accounts: AccountManager::new(),
+			accounts: AccountManager::default(),
+			java: JavaInventory::default(),
 			task_manager: Arc::new(TaskManager::new()),
 			instances: RwLock::new(Vec::new()),
 			current_instance: Mutex::new(None),
@@ -49,11 +64,26 @@ impl AppState {
 		})
 	}
 
+	/// 主游戏目录在前，其后是配置中的其他目录
+	pub fn cluster_paths(&self) -> Vec<PathBuf> {
+		let mut paths = vec![self.cluster_path()];
+		for path in self.config.get().extra_clusters {
+			if !paths.contains(&path) {
+				paths.push(path);
+			}
+		}
+		paths
+	}
+
 	pub fn scan_instances(&self) {
-		let path = self.cluster_path();
-		if let Ok(found) = crate::minecraft::game::instance::InstanceScanner::scan_cluster(&path) {
+		let paths = self.cluster_paths();
+		if let Ok(found) = InstanceScanner::scan_clusters(&paths) {
 			let mut guard = self.instances.write().unwrap();
-			tracing::info!("Scanned {} instances from {}", found.len(), path.display());
+			tracing::info!(
+				"Scanned {} instances from {} clusters",
+				found.len(),
+				paths.len()
+			);
 			*guard = found;
 		}
 	}
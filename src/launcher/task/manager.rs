@@ -2,12 +2,13 @@ use crate::launcher::task::error::{TaskError, TaskResult};
 use crate::launcher::task::executor::{BlockingExecutor, ConcurrentExecutor};
 use crate::launcher::task::handle::{TaskHandle, TaskId};
 use crate::launcher::task::lock::LockManager;
-use crate::launcher::task::framework::{BlockingTask, ConcurrentTask};
+use crate::launcher::task::framework::{BlockingTask, ConcurrentTask, TaskContext};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{Notify, RwLock, watch};
 
 pub struct TaskManager {
+	lock_manager: Arc<LockManager>,
 	blocking_executor: BlockingExecutor,
 	concurrent_executor: ConcurrentExecutor,
 	tasks: RwLock<HashMap<TaskId, TaskInfo>>,
@@ -23,7 +24,8 @@ impl TaskManager {
 		let lock_manager = Arc::new(LockManager::new());
 		Self {
 			blocking_executor: BlockingExecutor::new(Arc::clone(&lock_manager)),
-			concurrent_executor: ConcurrentExecutor::new(lock_manager, Some(5)),
+			concurrent_executor: ConcurrentExecutor::new(Arc::clone(&lock_manager), Some(5)),
+			lock_manager,
 			tasks: RwLock::new(HashMap::new()),
 		}
 	}
@@ -48,6 +50,23 @@ impl TaskManager {
 		Ok(handle)
 	}
 
+	/// 在父任务中直接运行子任务：持有子任务的锁、沿用父任务的取消信号，
+	/// 不占用并发额度，避免父任务等待子任务时占满执行器
+	pub async fn run_nested<T: ConcurrentTask>(
+		&self,
+		mut task: T,
+		ctx: &TaskContext,
+	) -> TaskResult<T::Output> {
+		let locks = task.locks();
+		self.lock_manager
+			.try_acquire(&locks)
+			.await
+			.map_err(TaskError::LockConflict)?;
+		let result = task.execute(ctx).await;
+		self.lock_manager.release(&locks).await;
+		result
+	}
+
 	pub async fn cancel(&self, task_id: TaskId) -> TaskResult<()> {
 		let tasks = self.tasks.read().await;
 		let info = tasks.get(&task_id).ok_or(TaskError::InvalidState)?;
@@ -6,6 +6,8 @@ mod config;
 mod infrastructure;
 mod launcher;
 mod minecraft;
+#[cfg(test)]
+mod test_util;
 mod ui;
 
 use launcher::core::logger;
@@ -0,0 +1,43 @@
+use reqwest::{Response, StatusCode};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+	#[error("http error: {0}")]
+	Http(#[from] reqwest::Error),
+	#[error("invalid response: {0}")]
+	Parse(#[from] serde_json::Error),
+	#[error("unexpected status {status}: {body}")]
+	UnexpectedStatus { status: StatusCode, body: String },
+	#[error("device code expired")]
+	Expired,
+	#[error("authorization declined by user")]
+	Declined,
+	#[error("xbox live: {0}")]
+	Xbox(String),
+	#[error("auth server: {0}")]
+	Server(String),
+	#[error("no minecraft profile on this account")]
+	NoProfile,
+	#[error("refresh token revoked or expired")]
+	Revoked,
+	#[error("account {0} not found")]
+	UnknownAccount(Uuid),
+	#[error("{0} is not supported for this account")]
+	Unsupported(&'static str),
+	#[error("image error: {0}")]
+	Image(#[from] image::ImageError),
+	#[error("io error: {0}")]
+	Io(#[from] std::io::Error),
+}
+
+pub(crate) async fn read_json<T: DeserializeOwned>(resp: Response) -> Result<T, AuthError> {
+	let status = resp.status();
+	let body = resp.text().await?;
+	if !status.is_success() {
+		return Err(AuthError::UnexpectedStatus { status, body });
+	}
+	Ok(serde_json::from_str(&body)?)
+}
@@ -0,0 +1,541 @@
+use super::error::{AuthError, read_json};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const SCOPE: &str = "XboxLive.signin offline_access";
+
+/// 各认证服务的基础地址，测试时可指向本地服务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MicrosoftEndpoints {
+	pub oauth: String,
+	pub xbox_user: String,
+	pub xsts: String,
+	pub minecraft: String,
+}
+
+impl Default for MicrosoftEndpoints {
+	fn default() -> Self {
+		Self {
+			oauth: "https://login.microsoftonline.com/consumers/oauth2/v2.0".into(),
+			xbox_user: "https://user.auth.xboxlive.com".into(),
+			xsts: "https://xsts.auth.xboxlive.com".into(),
+			minecraft: "https://api.minecraftservices.com".into(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceCode {
+	pub device_code: String,
+	pub user_code: String,
+	pub verification_uri: String,
+	pub expires_in: u64,
+	#[serde(default = "default_interval")]
+	pub interval: u64,
+}
+
+fn default_interval() -> u64 {
+	5
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MsaToken {
+	pub access_token: String,
+	#[serde(default)]
+	pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct XboxToken {
+	pub token: String,
+	pub user_hash: String,
+	/// 玩家代号，部分响应中不带
+	pub gamertag: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MinecraftToken {
+	pub access_token: String,
+	pub expires_in: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MinecraftProfile {
+	pub id: String,
+	pub name: String,
+}
+
+/// 完整登录流程的结果
+#[derive(Debug, Clone)]
+pub struct MicrosoftLogin {
+	pub msa: MsaToken,
+	pub minecraft: MinecraftToken,
+	pub profile: MinecraftProfile,
+	pub uuid: Uuid,
+	pub xuid: String,
+	/// 是否购买了 Minecraft: Java Edition
+	pub owns_game: bool,
+}
+
+#[derive(Deserialize)]
+struct Entitlements {
+	#[serde(default)]
+	items: Vec<EntitlementItem>,
+}
+
+#[derive(Deserialize)]
+struct EntitlementItem {
+	name: String,
+}
+
+#[derive(Deserialize)]
+struct TokenClaims {
+	#[serde(default)]
+	xuid: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OAuthError {
+	error: String,
+}
+
+#[derive(Deserialize)]
+struct XboxResponse {
+	#[serde(rename = "Token")]
+	token: String,
+	#[serde(rename = "DisplayClaims")]
+	display_claims: XboxDisplayClaims,
+}
+
+#[derive(Deserialize)]
+struct XboxDisplayClaims {
+	xui: Vec<XboxUserInfo>,
+}
+
+#[derive(Deserialize)]
+struct XboxUserInfo {
+	uhs: String,
+	#[serde(default)]
+	gtg: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct XboxError {
+	#[serde(rename = "XErr")]
+	xerr: u64,
+}
+
+pub struct MicrosoftAuth {
+	client: Client,
+	client_id: String,
+	endpoints: MicrosoftEndpoints,
+}
+
+impl MicrosoftAuth {
+	pub fn new(
+		client_id: impl Into<String>,
+		endpoints: MicrosoftEndpoints,
+	) -> Result<Self, AuthError> {
+		let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+		Ok(Self {
+			client,
+			client_id: client_id.into(),
+			endpoints,
+		})
+	}
+
+	/// 完整的设备码登录，`on_code` 用于把验证码展示给用户
+	pub async fn login<F>(&self, on_code: F) -> Result<MicrosoftLogin, AuthError>
+	where
+		F: FnOnce(&DeviceCode),
+	{
+		let code = self.request_device_code().await?;
+		on_code(&code);
+		let msa = self.poll_token(&code).await?;
+		self.login_with_msa(msa).await
+	}
+
+	/// 从 MSA 令牌开始完成 Xbox Live -> XSTS -> Minecraft 的认证链
+	pub async fn login_with_msa(&self, msa: MsaToken) -> Result<MicrosoftLogin, AuthError> {
+		let xbl = self.xbox_live(&msa.access_token).await?;
+		let xsts = self.xsts(&xbl.token).await?;
+		let minecraft = self.minecraft_login(&xsts).await?;
+		let owns_game = self.owns_game(&minecraft.access_token).await?;
+		let xuid = token_xuid(&minecraft.access_token).unwrap_or_default();
+		let profile = match self.profile(&minecraft.access_token).await {
+			// 未购买游戏的账户没有角色，以 Xbox 玩家代号进入试玩
+			Err(AuthError::NoProfile) if !owns_game => {
+				let name = xsts
+					.gamertag
+					.or(xbl.gamertag)
+					.ok_or_else(|| AuthError::Xbox("missing gamertag".into()))?;
+				demo_profile(name, &xuid)
+			}
+			result => result?,
+		};
+		let uuid = Uuid::parse_str(&profile.id)
+			.map_err(|e| AuthError::Xbox(format!("invalid profile id {}: {e}", profile.id)))?;
+
+		Ok(MicrosoftLogin {
+			msa,
+			minecraft,
+			profile,
+			uuid,
+			xuid,
+			owns_game,
+		})
+	}
+
+	pub async fn request_device_code(&self) -> Result<DeviceCode, AuthError> {
+		let resp = self
+			.client
+			.post(format!("{}/devicecode", self.endpoints.oauth))
+			.form(&[("client_id", self.client_id.as_str()), ("scope", SCOPE)])
+			.send()
+			.await?;
+		read_json(resp).await
+	}
+
+	pub async fn poll_token(&self, code: &DeviceCode) -> Result<MsaToken, AuthError> {
+		let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+		let mut interval = Duration::from_secs(code.interval);
+
+		loop {
+			if Instant::now() >= deadline {
+				return Err(AuthError::Expired);
+			}
+
+			let resp = self
+				.client
+				.post(format!("{}/token", self.endpoints.oauth))
+				.form(&[
+					("grant_type", DEVICE_CODE_GRANT),
+					("client_id", self.client_id.as_str()),
+					("device_code", code.device_code.as_str()),
+				])
+				.send()
+				.await?;
+
+			if resp.status().is_success() {
+				return read_json(resp).await;
+			}
+
+			let status = resp.status();
+			let body = resp.text().await?;
+			let error = serde_json::from_str::<OAuthError>(&body)
+				.map(|e| e.error)
+				.unwrap_or_default();
+			match error.as_str() {
+				"authorization_pending" => {}
+				"slow_down" => interval += Duration::from_secs(5),
+				"expired_token" => return Err(AuthError::Expired),
+				"authorization_declined" | "access_denied" => return Err(AuthError::Declined),
+				_ => return Err(AuthError::UnexpectedStatus { status, body }),
+			}
+
+			tokio::time::sleep(interval).await;
+		}
+	}
+
+	/// 用 MSA refresh token 换取新令牌，refresh token 失效时返回 `Revoked`
+	pub async fn refresh(&self, refresh_token: &str) -> Result<MsaToken, AuthError> {
+		let resp = self
+			.client
+			.post(format!("{}/token", self.endpoints.oauth))
+			.form(&[
+				("grant_type", "refresh_token"),
+				("client_id", self.client_id.as_str()),
+				("refresh_token", refresh_token),
+				("scope", SCOPE),
+			])
+			.send()
+			.await?;
+
+		if resp.status() == StatusCode::BAD_REQUEST || resp.status() == StatusCode::UNAUTHORIZED {
+			let status = resp.status();
+			let body = resp.text().await?;
+			let error = serde_json::from_str::<OAuthError>(&body)
+				.map(|e| e.error)
+				.unwrap_or_default();
+			return Err(match error.as_str() {
+				"invalid_grant" | "interaction_required" => AuthError::Revoked,
+				_ => AuthError::UnexpectedStatus { status, body },
+			});
+		}
+		read_json(resp).await
+	}
+
+	pub async fn xbox_live(&self, msa_token: &str) -> Result<XboxToken, AuthError> {
+		let body = serde_json::json!({
+			"Properties": {
+				"AuthMethod": "RPS",
+				"SiteName": "user.auth.xboxlive.com",
+				"RpsTicket": format!("d={msa_token}"),
+			},
+			"RelyingParty": "http://auth.xboxlive.com",
+			"TokenType": "JWT",
+		});
+		let url = format!("{}/user/authenticate", self.endpoints.xbox_user);
+		self.xbox_request(&url, &body).await
+	}
+
+	pub async fn xsts(&self, xbl_token: &str) -> Result<XboxToken, AuthError> {
+		let body = serde_json::json!({
+			"Properties": {
+				"SandboxId": "RETAIL",
+				"UserTokens": [xbl_token],
+			},
+			"RelyingParty": "rp://api.minecraftservices.com/",
+			"TokenType": "JWT",
+		});
+		let url = format!("{}/xsts/authorize", self.endpoints.xsts);
+		self.xbox_request(&url, &body).await
+	}
+
+	async fn xbox_request(
+		&self,
+		url: &str,
+		body: &serde_json::Value,
+	) -> Result<XboxToken, AuthError> {
+		let resp = self
+			.client
+			.post(url)
+			.header(CONTENT_TYPE, "application/json")
+			.header(ACCEPT, "application/json")
+			.body(serde_json::to_vec(body)?)
+			.send()
+			.await?;
+
+		if resp.status() == StatusCode::UNAUTHORIZED {
+			let body = resp.text().await?;
+			return Err(match serde_json::from_str::<XboxError>(&body) {
+				Ok(e) => AuthError::Xbox(xerr_message(e.xerr)),
+				Err(_) => AuthError::UnexpectedStatus {
+					status: StatusCode::UNAUTHORIZED,
+					body,
+				},
+			});
+		}
+
+		let parsed: XboxResponse = read_json(resp).await?;
+		let user = parsed
+			.display_claims
+			.xui
+			.into_iter()
+			.next()
+			.ok_or_else(|| AuthError::Xbox("missing user hash".into()))?;
+
+		Ok(XboxToken {
+			token: parsed.token,
+			user_hash: user.uhs,
+			gamertag: user.gtg,
+		})
+	}
+
+	pub async fn minecraft_login(&self, xsts: &XboxToken) -> Result<MinecraftToken, AuthError> {
+		let body = serde_json::json!({
+			"identityToken": format!("XBL3.0 x={};{}", xsts.user_hash, xsts.token),
+		});
+		let resp = self
+			.client
+			.post(format!(
+				"{}/authentication/login_with_xbox",
+				self.endpoints.minecraft
+			))
+			.header(CONTENT_TYPE, "application/json")
+			.header(ACCEPT, "application/json")
+			.body(serde_json::to_vec(&body)?)
+			.send()
+			.await?;
+		read_json(resp).await
+	}
+
+	/// 查询商店权益，Game Pass 同样会带有 `game_minecraft`
+	pub async fn owns_game(&self, mc_token: &str) -> Result<bool, AuthError> {
+		let resp = self
+			.client
+			.get(format!("{}/entitlements/mcstore", self.endpoints.minecraft))
+			.header(AUTHORIZATION, format!("Bearer {mc_token}"))
+			.send()
+			.await?;
+		let entitlements: Entitlements = read_json(resp).await?;
+		Ok(entitlements
+			.items
+			.iter()
+			.any(|i| i.name == "product_minecraft" || i.name == "game_minecraft"))
+	}
+
+	pub async fn profile(&self, mc_token: &str) -> Result<MinecraftProfile, AuthError> {
+		let resp = self
+			.client
+			.get(format!("{}/minecraft/profile", self.endpoints.minecraft))
+			.header(AUTHORIZATION, format!("Bearer {mc_token}"))
+			.send()
+			.await?;
+
+		if resp.status() == StatusCode::NOT_FOUND {
+			return Err(AuthError::NoProfile);
+		}
+		read_json(resp).await
+	}
+}
+
+// 试玩角色的 UUID 由 xuid 派生，没有 xuid 时退回玩家代号，重复登录时保持不变
+fn demo_profile(name: String, xuid: &str) -> MinecraftProfile {
+	let seed = if xuid.is_empty() { &name } else { xuid };
+	let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, format!("hako-demo:{seed}").as_bytes());
+	MinecraftProfile {
+		id: uuid.simple().to_string(),
+		name,
+	}
+}
+
+// Minecraft 令牌是 JWT，xuid 在 payload 中
+fn token_xuid(token: &str) -> Option<String> {
+	let payload = token.split('.').nth(1)?;
+	let data = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+	serde_json::from_slice::<TokenClaims>(&data).ok()?.xuid
+}
+
+fn xerr_message(code: u64) -> String {
+	match code {
+		2148916233 => "this Microsoft account has no Xbox profile".into(),
+		2148916235 => "Xbox Live is not available in this region".into(),
+		2148916236 | 2148916237 => "account needs adult verification".into(),
+		2148916238 => "child account must be added to a family".into(),
+		_ => format!("XErr {code}"),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::{request_path, serve};
+
+	async fn start_auth_server() -> String {
+		let mut polls = 0;
+		serve(move |req| match request_path(req) {
+			"/oauth/devicecode" => (
+				"200 OK",
+				r#"{"device_code":"dc","user_code":"ABCD","verification_uri":"http://verify","expires_in":60,"interval":0}"#.to_string(),
+			),
+			"/oauth/token" => {
+				polls += 1;
+				if polls < 2 {
+					("400 Bad Request", r#"{"error":"authorization_pending"}"#.to_string())
+				} else {
+					(
+						"200 OK",
+						r#"{"access_token":"msa","refresh_token":"rt","expires_in":3600}"#.to_string(),
+					)
+				}
+			}
+			"/xbl/user/authenticate" => (
+				"200 OK",
+				r#"{"Token":"xbl","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#.to_string(),
+			),
+			"/xsts/xsts/authorize" => {
+				assert!(req.contains("\"xbl\""));
+				(
+					"200 OK",
+					r#"{"Token":"xsts","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#.to_string(),
+				)
+			}
+			"/mc/authentication/login_with_xbox" => {
+				assert!(req.contains("XBL3.0 x=hash;xsts"));
+				(
+					"200 OK",
+					r#"{"access_token":"mc","expires_in":86400}"#.to_string(),
+				)
+			}
+			"/mc/entitlements/mcstore" => (
+				"200 OK",
+				r#"{"items":[{"name":"product_minecraft","signature":"x"},{"name":"game_minecraft","signature":"x"}]}"#.to_string(),
+			),
+			"/mc/minecraft/profile" => {
+				assert!(req.contains("Bearer mc"));
+				(
+					"200 OK",
+					r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch"}"#.to_string(),
+				)
+			}
+			_ => ("404 Not Found", String::new()),
+		})
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_device_code_login() {
+		let url = start_auth_server().await;
+		let endpoints = MicrosoftEndpoints {
+			oauth: format!("{url}/oauth"),
+			xbox_user: format!("{url}/xbl"),
+			xsts: format!("{url}/xsts"),
+			minecraft: format!("{url}/mc"),
+		};
+
+		let auth = MicrosoftAuth::new("client", endpoints).unwrap();
+		let mut shown = None;
+		let login = auth
+			.login(|code| shown = Some(code.user_code.clone()))
+			.await
+			.unwrap();
+
+		assert_eq!(shown.as_deref(), Some("ABCD"));
+		assert_eq!(login.profile.name, "Notch");
+		assert_eq!(login.minecraft.access_token, "mc");
+		assert_eq!(login.msa.refresh_token.as_deref(), Some("rt"));
+		assert!(login.owns_game);
+		assert_eq!(
+			login.uuid,
+			Uuid::parse_str("069a79f4-44e9-4726-a5be-fca90e38aaf5").unwrap()
+		);
+	}
+
+	#[tokio::test]
+	async fn test_demo_login_without_profile() {
+		let url = serve(|req| match request_path(req) {
+			"/xbl/user/authenticate" => (
+				"200 OK",
+				r#"{"Token":"xbl","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#.to_string(),
+			),
+			"/xsts/xsts/authorize" => (
+				"200 OK",
+				r#"{"Token":"xsts","DisplayClaims":{"xui":[{"uhs":"hash","gtg":"DemoPlayer"}]}}"#
+					.to_string(),
+			),
+			"/mc/authentication/login_with_xbox" => (
+				"200 OK",
+				r#"{"access_token":"mc","expires_in":86400}"#.to_string(),
+			),
+			"/mc/entitlements/mcstore" => ("200 OK", r#"{"items":[]}"#.to_string()),
+			_ => ("404 Not Found", String::new()),
+		})
+		.await;
+		let endpoints = MicrosoftEndpoints {
+			oauth: format!("{url}/oauth"),
+			xbox_user: format!("{url}/xbl"),
+			xsts: format!("{url}/xsts"),
+			minecraft: format!("{url}/mc"),
+		};
+		let auth = MicrosoftAuth::new("client", endpoints).unwrap();
+		let msa = MsaToken {
+			access_token: "msa".into(),
+			refresh_token: Some("rt".into()),
+		};
+
+		let login = auth.login_with_msa(msa.clone()).await.unwrap();
+		assert!(!login.owns_game);
+		assert_eq!(login.profile.name, "DemoPlayer");
+		let again = auth.login_with_msa(msa).await.unwrap();
+		assert_eq!(login.uuid, again.uuid);
+	}
+}
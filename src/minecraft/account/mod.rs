@@ -1,6 +1,28 @@
+pub mod error;
+pub mod microsoft;
+pub mod offline;
+pub mod skin;
+pub mod store;
+pub mod yggdrasil;
+
+use crate::launcher::core::paths;
+use anyhow::Result;
+use error::AuthError;
+use microsoft::{DeviceCode, MicrosoftAuth, MicrosoftEndpoints};
+use offline::{OfflineUuid, OfflineUuidScheme};
 use serde::{Deserialize, Serialize};
+use skin::{Appearance, SkinClient, SkinModel};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use std::sync::RwLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use store::AccountStore;
 use uuid::Uuid;
+use yggdrasil::{YggdrasilClient, YggdrasilProfile, parse_profile_id};
+
+/// 令牌剩余有效期低于此值时即视为需要刷新
+pub const REFRESH_MARGIN: Duration = Duration::from_secs(30 * 60);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -8,114 +30,706 @@ pub enum Account {
 	Offline {
 		username: String,
 		uuid: Uuid,
+		#[serde(default)]
+		scheme: OfflineUuidScheme,
 	},
 	Microsoft {
 		username: String,
 		uuid: Uuid,
 		access_token: String,
+		#[serde(default)]
+		refresh_token: String,
+		#[serde(default)]
+		client_id: String,
+		#[serde(default)]
+		xuid: String,
+		/// Minecraft 令牌过期时间（Unix 秒）
+		#[serde(default)]
+		expires_at: u64,
+		#[serde(default)]
+		revoked: bool,
+		/// 未购买游戏的账户以试玩模式启动
+		#[serde(default = "default_owns_game")]
+		owns_game: bool,
+	},
+	/// 第三方 Yggdrasil 认证服务器（通过 authlib-injector 启动）
+	Yggdrasil {
+		username: String,
+		uuid: Uuid,
+		/// API 根地址
+		server: String,
+		access_token: String,
+		client_token: String,
+		#[serde(default)]
+		revoked: bool,
 	},
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+	Valid,
+	/// 令牌已过期，但 refresh token 仍可尝试续期
+	Expired,
+	/// refresh token 已失效，必须重新登录
+	Revoked,
+}
+
 impl Account {
 	pub fn offline(username: impl Into<String>) -> Self {
+		Self::offline_with(username, OfflineUuid::Vanilla)
+	}
+
+	pub fn offline_with(username: impl Into<String>, uuid: OfflineUuid) -> Self {
 		let username = username.into();
-		let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, username.as_bytes());
-		Self::Offline { username, uuid }
+		let (uuid, scheme) = uuid.resolve(&username);
+		Self::Offline {
+			username,
+			uuid,
+			scheme,
+		}
 	}
 
 	pub fn username(&self) -> &str {
 		match self {
-			Self::Offline { username, .. } | Self::Microsoft { username, .. } => username,
+			Self::Offline { username, .. }
+			| Self::Microsoft { username, .. }
+			| Self::Yggdrasil { username, .. } => username,
 		}
 	}
 
 	pub fn uuid(&self) -> &Uuid {
 		match self {
-			Self::Offline { uuid, .. } | Self::Microsoft { uuid, .. } => uuid,
+			Self::Offline { uuid, .. }
+			| Self::Microsoft { uuid, .. }
+			| Self::Yggdrasil { uuid, .. } => uuid,
 		}
 	}
 
 	pub fn access_token(&self) -> Option<&str> {
 		match self {
-			Self::Microsoft { access_token, .. } => Some(access_token),
-			_ => None,
+			Self::Microsoft { access_token, .. } | Self::Yggdrasil { access_token, .. } => {
+				Some(access_token)
+			}
+			Self::Offline { .. } => None,
 		}
 	}
 
 	pub fn is_offline(&self) -> bool {
 		matches!(self, Self::Offline { .. })
 	}
+
+	/// 正版账户未拥有游戏时需以试玩模式启动
+	pub fn is_demo(&self) -> bool {
+		matches!(
+			self,
+			Self::Microsoft {
+				owns_game: false,
+				..
+			}
+		)
+	}
+
+	pub fn session_state(&self) -> SessionState {
+		match self {
+			Self::Offline { .. } => SessionState::Valid,
+			Self::Microsoft {
+				access_token,
+				refresh_token,
+				expires_at,
+				revoked,
+				..
+			} => {
+				if *revoked || (access_token.is_empty() && refresh_token.is_empty()) {
+					SessionState::Revoked
+				} else if access_token.is_empty() || unix_now() >= *expires_at {
+					SessionState::Expired
+				} else {
+					SessionState::Valid
+				}
+			}
+			Self::Yggdrasil {
+				access_token,
+				revoked,
+				..
+			} => {
+				if *revoked {
+					SessionState::Revoked
+				} else if access_token.is_empty() {
+					SessionState::Expired
+				} else {
+					SessionState::Valid
+				}
+			}
+		}
+	}
+
+	/// 同一服务上的同一角色，重复登录时据此沿用原有 ID
+	fn same_identity(&self, other: &Account) -> bool {
+		match (self, other) {
+			(Self::Microsoft { uuid: a, .. }, Self::Microsoft { uuid: b, .. }) => a == b,
+			(
+				Self::Yggdrasil {
+					uuid: a,
+					server: sa,
+					..
+				},
+				Self::Yggdrasil {
+					uuid: b,
+					server: sb,
+					..
+				},
+			) => a == b && sa == sb,
+			_ => false,
+		}
+	}
+
+	/// 令牌即将过期，需要在启动前或后台刷新
+	pub fn needs_refresh(&self) -> bool {
+		match self {
+			// Yggdrasil 令牌没有过期时间，只在启动前校验
+			Self::Offline { .. } | Self::Yggdrasil { .. } => false,
+			Self::Microsoft {
+				expires_at,
+				revoked,
+				..
+			} => !*revoked && unix_now() + REFRESH_MARGIN.as_secs() >= *expires_at,
+		}
+	}
+}
+
+pub type AccountId = Uuid;
+
+#[derive(Debug, Clone)]
+pub struct AccountEntry {
+	pub id: AccountId,
+	pub account: Account,
 }
 
 pub struct AccountManager {
-	accounts: RwLock<Vec<Account>>,
-	current: RwLock<Option<usize>>,
+	accounts: RwLock<Vec<AccountEntry>>,
+	current: RwLock<Option<AccountId>>,
+	endpoints: MicrosoftEndpoints,
+	store: Option<AccountStore>,
+	/// 皮肤贴图与头像缓存目录
+	skin_dir: PathBuf,
+	/// 最近一次从服务器取得的外观，仅保存在内存中
+	appearances: RwLock<HashMap<AccountId, Appearance>>,
 }
 
 impl AccountManager {
-	pub fn new() -> Self {
+	pub fn new() -> Result<Self> {
+		Self::open(paths::config_dir()?.join("accounts.yml"))
+	}
+
+	pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+		let path = path.into();
+		let skin_dir = path.with_file_name("skins");
+		let store = AccountStore::open(path)?;
+		let state = store.load()?;
+		Ok(Self {
+			accounts: RwLock::new(state.accounts),
+			current: RwLock::new(state.current),
+			endpoints: MicrosoftEndpoints::default(),
+			store: Some(store),
+			skin_dir,
+			appearances: RwLock::new(HashMap::new()),
+		})
+	}
+
+	/// 不落盘的账户管理器
+	pub fn in_memory() -> Self {
 		Self {
 			accounts: RwLock::new(Vec::new()),
 			current: RwLock::new(None),
+			endpoints: MicrosoftEndpoints::default(),
+			store: None,
+			skin_dir: std::env::temp_dir().join("hako_skins"),
+			appearances: RwLock::new(HashMap::new()),
 		}
 	}
 
-	pub fn add_offline(&self, username: impl Into<String>) -> usize {
-		let account = Account::offline(username);
-		let mut accounts = self.accounts.write().unwrap();
-		let idx = accounts.len();
-		accounts.push(account);
-		*self.current.write().unwrap() = Some(idx);
-		idx
+	#[cfg(test)]
+	pub fn with_endpoints(mut self, endpoints: MicrosoftEndpoints) -> Self {
+		self.endpoints = endpoints;
+		self
 	}
 
-	pub fn current(&self) -> Option<Account> {
-		let idx = (*self.current.read().unwrap())?;
-		self.accounts.read().unwrap().get(idx).cloned()
+	pub fn add_offline_with(&self, username: impl Into<String>, uuid: OfflineUuid) -> AccountId {
+		self.insert(Account::offline_with(username, uuid))
 	}
 
-	pub fn select(&self, idx: Option<usize>) {
-		*self.current.write().unwrap() = idx;
+	/// 更换离线账户的 UUID，返回 (旧, 新)；存档数据需由调用方先行迁移
+	pub fn set_offline_uuid(&self, id: AccountId, uuid: OfflineUuid) -> Option<(Uuid, Uuid)> {
+		let Some(Account::Offline {
+			username,
+			uuid: old,
+			..
+		}) = self.get(id)
+		else {
+			return None;
+		};
+		let account = Account::offline_with(username, uuid);
+		let new = *account.uuid();
+		self.update(id, account);
+		Some((old, new))
 	}
 
-	pub fn list(&self) -> Vec<Account> {
+	pub fn current_id(&self) -> Option<AccountId> {
+		*self.current.read().unwrap()
+	}
+
+	pub fn get(&self, id: AccountId) -> Option<Account> {
+		self.accounts
+			.read()
+			.unwrap()
+			.iter()
+			.find(|e| e.id == id)
+			.map(|e| e.account.clone())
+	}
+
+	pub fn select(&self, id: Option<AccountId>) {
+		*self.current.write().unwrap() = id.filter(|id| self.get(*id).is_some());
+		self.persist();
+	}
+
+	pub fn list(&self) -> Vec<AccountEntry> {
 		self.accounts.read().unwrap().clone()
 	}
 
-	pub fn remove(&self, idx: usize) {
-		let mut accounts = self.accounts.write().unwrap();
-		if idx < accounts.len() {
-			accounts.remove(idx);
-			let mut current = self.current.write().unwrap();
-			if *current == Some(idx) {
-				*current = None;
-			} else if let Some(c) = *current {
-				if c > idx {
-					*current = Some(c - 1);
+	pub fn remove(&self, id: AccountId) {
+		self.accounts.write().unwrap().retain(|e| e.id != id);
+		let mut current = self.current.write().unwrap();
+		if *current == Some(id) {
+			*current = None;
+		}
+		drop(current);
+		self.persist();
+		let _ = fs::remove_file(self.skin_path(id));
+		let _ = fs::remove_file(self.avatar_path(id));
+		self.appearances.write().unwrap().remove(&id);
+	}
+
+	pub fn skin_path(&self, id: AccountId) -> PathBuf {
+		self.skin_dir.join(format!("{id}.png"))
+	}
+
+	pub fn avatar_path(&self, id: AccountId) -> PathBuf {
+		self.skin_dir.join(format!("{id}-head.png"))
+	}
+
+	/// 已缓存的头像，尚未同步过皮肤时为 None
+	pub fn cached_avatar(&self, id: AccountId) -> Option<PathBuf> {
+		Some(self.avatar_path(id)).filter(|p| p.exists())
+	}
+
+	/// 上次同步得到的外观，用于列出可选披风
+	pub fn cached_appearance(&self, id: AccountId) -> Option<Appearance> {
+		self.appearances.read().unwrap().get(&id).cloned()
+	}
+
+	pub async fn appearance(&self, id: AccountId) -> std::result::Result<Appearance, AuthError> {
+		let client = SkinClient::new()?;
+		let appearance = match self.get(id).ok_or(AuthError::UnknownAccount(id))? {
+			Account::Offline { .. } => Err(AuthError::Unsupported("online appearance")),
+			Account::Microsoft { access_token, .. } => {
+				client
+					.microsoft_appearance(&self.endpoints.minecraft, &access_token)
+					.await
+			}
+			Account::Yggdrasil { server, uuid, .. } => {
+				client.yggdrasil_appearance(&server, &uuid).await
+			}
+		}?;
+		Ok(self.cache_appearance(id, appearance))
+	}
+
+	/// 下载当前皮肤到本地缓存并生成头像，没有皮肤时返回 None
+	pub async fn sync_skin(
+		&self,
+		id: AccountId,
+	) -> std::result::Result<Option<PathBuf>, AuthError> {
+		let appearance = self.appearance(id).await?;
+		let Some(skin) = appearance.skin else {
+			return Ok(None);
+		};
+		let png = SkinClient::new()?.download_texture(&skin.url).await?;
+		self.cache_skin(id, &png).map(Some)
+	}
+
+	pub async fn upload_skin(
+		&self,
+		id: AccountId,
+		png: Vec<u8>,
+		model: SkinModel,
+	) -> std::result::Result<Appearance, AuthError> {
+		skin::validate_skin(&png)?;
+		let client = SkinClient::new()?;
+		let appearance = match self.get(id).ok_or(AuthError::UnknownAccount(id))? {
+			Account::Offline { .. } => return Err(AuthError::Unsupported("skin upload")),
+			Account::Microsoft { access_token, .. } => {
+				client
+					.microsoft_upload_skin(
+						&self.endpoints.minecraft,
+						&access_token,
+						png.clone(),
+						model,
+					)
+					.await?
+			}
+			Account::Yggdrasil {
+				server,
+				uuid,
+				access_token,
+				..
+			} => {
+				client
+					.yggdrasil_upload_skin(&server, &access_token, &uuid, png.clone(), model)
+					.await?;
+				client.yggdrasil_appearance(&server, &uuid).await?
+			}
+		};
+		self.cache_skin(id, &png)?;
+		Ok(self.cache_appearance(id, appearance))
+	}
+
+	/// 切换正版披风，`cape_id` 为 None 时隐藏披风
+	pub async fn select_cape(
+		&self,
+		id: AccountId,
+		cape_id: Option<&str>,
+	) -> std::result::Result<Appearance, AuthError> {
+		let Account::Microsoft { access_token, .. } =
+			self.get(id).ok_or(AuthError::UnknownAccount(id))?
+		else {
+			return Err(AuthError::Unsupported("cape selection"));
+		};
+		let appearance = SkinClient::new()?
+			.microsoft_select_cape(&self.endpoints.minecraft, &access_token, cape_id)
+			.await?;
+		Ok(self.cache_appearance(id, appearance))
+	}
+
+	fn cache_appearance(&self, id: AccountId, appearance: Appearance) -> Appearance {
+		self.appearances
+			.write()
+			.unwrap()
+			.insert(id, appearance.clone());
+		appearance
+	}
+
+	fn cache_skin(&self, id: AccountId, png: &[u8]) -> std::result::Result<PathBuf, AuthError> {
+		fs::create_dir_all(&self.skin_dir)?;
+		let avatar = skin::head_avatar(png, 64)?;
+		fs::write(self.skin_path(id), png)?;
+		fs::write(self.avatar_path(id), skin::encode_png(&avatar)?)?;
+		Ok(self.skin_path(id))
+	}
+
+	// 设备码 -> MSA -> Xbox Live -> XSTS -> Minecraft -> profile
+	pub async fn login_microsoft<F>(
+		&self,
+		client_id: &str,
+		on_code: F,
+	) -> std::result::Result<AccountId, AuthError>
+	where
+		F: FnOnce(&DeviceCode),
+	{
+		let auth = MicrosoftAuth::new(client_id, self.endpoints.clone())?;
+		let login = auth.login(on_code).await?;
+
+		Ok(self.insert(microsoft_account(client_id, login)))
+	}
+
+	/// 第三方服务器登录，`server` 可以是 API 根或支持 ALI 的网站地址
+	pub async fn login_yggdrasil(
+		&self,
+		server: &str,
+		username: &str,
+		password: &str,
+	) -> std::result::Result<AccountId, AuthError> {
+		let client = YggdrasilClient::resolve(server).await?;
+		let client_token = Uuid::new_v4().simple().to_string();
+		let mut session = client
+			.authenticate(username, password, &client_token)
+			.await?;
+
+		// 有多个角色但未选定时，需要用 refresh 绑定角色
+		if session.selected_profile.is_none() {
+			let profile = session.profile().cloned().ok_or(AuthError::NoProfile)?;
+			session = client
+				.refresh(&session.access_token, &session.client_token, Some(&profile))
+				.await?;
+		}
+
+		let profile = session.profile().ok_or(AuthError::NoProfile)?;
+		Ok(self.insert(Account::Yggdrasil {
+			username: profile.name.clone(),
+			uuid: parse_profile_id(&profile.id)?,
+			server: client.server().to_string(),
+			access_token: session.access_token,
+			client_token: session.client_token,
+			revoked: false,
+		}))
+	}
+
+	/// 刷新账户令牌；正版账户在 `force` 为 false 时仅在临近过期时刷新，
+	/// Yggdrasil 账户先校验再按需刷新
+	pub async fn refresh(
+		&self,
+		id: AccountId,
+		force: bool,
+	) -> std::result::Result<SessionState, AuthError> {
+		let Some(account) = self.get(id) else {
+			return Err(AuthError::UnknownAccount(id));
+		};
+
+		let result = match &account {
+			Account::Offline { .. } => return Ok(SessionState::Valid),
+			Account::Microsoft { revoked: true, .. } | Account::Yggdrasil { revoked: true, .. } => {
+				return Ok(SessionState::Revoked);
+			}
+			Account::Microsoft { .. } if !force && !account.needs_refresh() => {
+				return Ok(account.session_state());
+			}
+			Account::Microsoft {
+				refresh_token,
+				client_id,
+				..
+			} => {
+				if refresh_token.is_empty() {
+					Err(AuthError::Revoked)
+				} else {
+					self.refresh_microsoft(client_id, refresh_token).await
 				}
 			}
+			Account::Yggdrasil { .. } => self.refresh_yggdrasil(&account, force).await,
+		};
+
+		match result {
+			Ok(Some(refreshed)) => {
+				self.update(id, refreshed);
+				Ok(SessionState::Valid)
+			}
+			Ok(None) => Ok(SessionState::Valid),
+			Err(AuthError::Revoked) => {
+				self.mark_revoked(id);
+				Ok(SessionState::Revoked)
+			}
+			Err(e) => Err(e),
 		}
 	}
 
-	// TODO: Microsoft OAuth 登录
-	pub async fn login_microsoft(&self, client_id: &str) -> Result<usize, ()> {
-		todo!("")
-		//     1. 设备码流程获取 device_code
-		//     2. 用户授权后获取 access_token
-		//     3. Xbox Live 认证
-		//     4. XSTS 认证
-		//     5. Minecraft 认证获取 MC access_token
-		//     6. 获取用户 profile (username, uuid)
-		//     7. 存储到 keyring
+	async fn refresh_microsoft(
+		&self,
+		client_id: &str,
+		refresh_token: &str,
+	) -> std::result::Result<Option<Account>, AuthError> {
+		let auth = MicrosoftAuth::new(client_id, self.endpoints.clone())?;
+		let mut msa = auth.refresh(refresh_token).await?;
+		// 响应中不一定带新的 refresh token，此时旧的仍然有效
+		if msa.refresh_token.is_none() {
+			msa.refresh_token = Some(refresh_token.to_string());
+		}
+		let login = auth.login_with_msa(msa).await?;
+		Ok(Some(microsoft_account(client_id, login)))
+	}
+
+	async fn refresh_yggdrasil(
+		&self,
+		account: &Account,
+		force: bool,
+	) -> std::result::Result<Option<Account>, AuthError> {
+		let Account::Yggdrasil {
+			username,
+			uuid,
+			server,
+			access_token,
+			client_token,
+			..
+		} = account
+		else {
+			return Ok(None);
+		};
+
+		let client = YggdrasilClient::new(server.as_str())?;
+		if !force && client.validate(access_token, client_token).await? {
+			return Ok(None);
+		}
+
+		let profile = YggdrasilProfile {
+			id: uuid.simple().to_string(),
+			name: username.clone(),
+		};
+		let session = client
+			.refresh(access_token, client_token, Some(&profile))
+			.await?;
+		Ok(Some(Account::Yggdrasil {
+			username: username.clone(),
+			uuid: *uuid,
+			server: server.clone(),
+			access_token: session.access_token,
+			client_token: session.client_token,
+			revoked: false,
+		}))
+	}
+
+	fn update(&self, id: AccountId, account: Account) {
+		let mut accounts = self.accounts.write().unwrap();
+		if let Some(entry) = accounts.iter_mut().find(|e| e.id == id) {
+			entry.account = account;
+		}
+		drop(accounts);
+		self.persist();
+	}
+
+	fn mark_revoked(&self, id: AccountId) {
+		let mut accounts = self.accounts.write().unwrap();
+		if let Some(Account::Microsoft { revoked, .. } | Account::Yggdrasil { revoked, .. }) =
+			accounts
+				.iter_mut()
+				.find(|e| e.id == id)
+				.map(|e| &mut e.account)
+		{
+			*revoked = true;
+		}
+		drop(accounts);
+		self.persist();
+	}
+
+	fn insert(&self, account: Account) -> AccountId {
+		let mut accounts = self.accounts.write().unwrap();
+		let existing = accounts
+			.iter_mut()
+			.find(|e| e.account.same_identity(&account));
+		let id = match existing {
+			Some(entry) => {
+				entry.account = account;
+				entry.id
+			}
+			None => {
+				let id = Uuid::new_v4();
+				accounts.push(AccountEntry { id, account });
+				id
+			}
+		};
+		drop(accounts);
+		*self.current.write().unwrap() = Some(id);
+		self.persist();
+		id
+	}
+
+	fn persist(&self) {
+		let Some(store) = &self.store else {
+			return;
+		};
+		let accounts = self.accounts.read().unwrap();
+		let current = *self.current.read().unwrap();
+		if let Err(e) = store.save(&accounts, current) {
+			tracing::error!("Failed to save accounts: {}", e);
+		}
 	}
 }
 
 impl Default for AccountManager {
 	fn default() -> Self {
-		Self::new()
+		Self::new().unwrap_or_else(|e| {
+			tracing::warn!("Failed to load accounts, using in-memory store: {}", e);
+			Self::in_memory()
+		})
 	}
 }
 
-pub fn offline_uuid(username: &str) -> Uuid {
-	Uuid::new_v5(&Uuid::NAMESPACE_OID, username.as_bytes())
+fn microsoft_account(client_id: &str, login: microsoft::MicrosoftLogin) -> Account {
+	Account::Microsoft {
+		username: login.profile.name,
+		uuid: login.uuid,
+		access_token: login.minecraft.access_token,
+		refresh_token: login.msa.refresh_token.unwrap_or_default(),
+		client_id: client_id.to_string(),
+		xuid: login.xuid,
+		expires_at: unix_now() + login.minecraft.expires_in,
+		revoked: false,
+		owns_game: login.owns_game,
+	}
+}
+
+// 旧版本保存的账户没有记录权益，按已拥有处理
+pub(crate) fn default_owns_game() -> bool {
+	true
+}
+
+pub fn unix_now() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs())
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::{request_path, serve};
+
+	#[tokio::test]
+	async fn test_refresh_keeps_refresh_token() {
+		let url = serve(|req| match request_path(req) {
+			"/oauth/token" => {
+				assert!(req.contains("refresh_token=old-rt"));
+				(
+					"200 OK",
+					r#"{"access_token":"msa","expires_in":3600}"#.to_string(),
+				)
+			}
+			"/xbl/user/authenticate" | "/xsts/xsts/authorize" => (
+				"200 OK",
+				r#"{"Token":"xbox","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#.to_string(),
+			),
+			"/mc/authentication/login_with_xbox" => (
+				"200 OK",
+				r#"{"access_token":"new-mc","expires_in":86400}"#.to_string(),
+			),
+			"/mc/entitlements/mcstore" => (
+				"200 OK",
+				r#"{"items":[{"name":"game_minecraft"}]}"#.to_string(),
+			),
+			"/mc/minecraft/profile" => (
+				"200 OK",
+				r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch"}"#.to_string(),
+			),
+			_ => ("404 Not Found", String::new()),
+		})
+		.await;
+		let manager = AccountManager::in_memory().with_endpoints(MicrosoftEndpoints {
+			oauth: format!("{url}/oauth"),
+			xbox_user: format!("{url}/xbl"),
+			xsts: format!("{url}/xsts"),
+			minecraft: format!("{url}/mc"),
+		});
+		let id = manager.insert(Account::Microsoft {
+			username: "Notch".into(),
+			uuid: Uuid::parse_str("069a79f444e94726a5befca90e38aaf5").unwrap(),
+			access_token: "old-mc".into(),
+			refresh_token: "old-rt".into(),
+			client_id: "client".into(),
+			xuid: String::new(),
+			expires_at: 0,
+			revoked: false,
+			owns_game: true,
+		});
+
+		assert_eq!(
+			manager.refresh(id, true).await.unwrap(),
+			SessionState::Valid
+		);
+		let Some(Account::Microsoft {
+			access_token,
+			refresh_token,
+			..
+		}) = manager.get(id)
+		else {
+			panic!("account missing");
+		};
+		assert_eq!(access_token, "new-mc");
+		assert_eq!(refresh_token, "old-rt");
+	}
 }
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::{Builder, Uuid};
+
+/// 离线账户 UUID 的生成方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum OfflineUuidScheme {
+	/// 与原版服务端一致：`"OfflinePlayer:" + name` 的 MD5（v3）
+	#[default]
+	Vanilla,
+	/// Hako 早期版本使用的 v5 UUID
+	Legacy,
+	/// 用户手动指定
+	Custom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfflineUuid {
+	Vanilla,
+	Legacy,
+	Custom(Uuid),
+}
+
+impl OfflineUuid {
+	pub fn resolve(self, username: &str) -> (Uuid, OfflineUuidScheme) {
+		match self {
+			Self::Vanilla => (vanilla_uuid(username), OfflineUuidScheme::Vanilla),
+			Self::Legacy => (legacy_uuid(username), OfflineUuidScheme::Legacy),
+			Self::Custom(uuid) => (uuid, OfflineUuidScheme::Custom),
+		}
+	}
+}
+
+/// 等价于 Java 的 `UUID.nameUUIDFromBytes(("OfflinePlayer:" + name).getBytes(UTF_8))`
+pub fn vanilla_uuid(username: &str) -> Uuid {
+	let digest = Md5::digest(format!("OfflinePlayer:{username}").as_bytes());
+	Builder::from_md5_bytes(digest.into()).into_uuid()
+}
+
+pub fn legacy_uuid(username: &str) -> Uuid {
+	Uuid::new_v5(&Uuid::NAMESPACE_OID, username.as_bytes())
+}
+
+#[derive(Debug, Default)]
+pub struct PlayerdataMigration {
+	pub moved: Vec<PathBuf>,
+	/// 目标文件已存在而跳过的文件
+	pub conflicts: Vec<PathBuf>,
+}
+
+/// 把各存档中旧 UUID 的玩家数据、进度和统计重命名为新 UUID
+pub fn migrate_playerdata(game_dir: &Path, old: Uuid, new: Uuid) -> Result<PlayerdataMigration> {
+	let mut report = PlayerdataMigration::default();
+	if old == new {
+		return Ok(report);
+	}
+
+	let saves = game_dir.join("saves");
+	if !saves.exists() {
+		return Ok(report);
+	}
+
+	let old_id = old.hyphenated().to_string();
+	let new_id = new.hyphenated().to_string();
+
+	for world in fs::read_dir(&saves).context("read saves")?.flatten() {
+		let world = world.path();
+		if !world.is_dir() {
+			continue;
+		}
+		for (dir, ext) in [
+			("playerdata", "dat"),
+			("playerdata", "dat_old"),
+			("advancements", "json"),
+			("stats", "json"),
+		] {
+			let from = world.join(dir).join(format!("{old_id}.{ext}"));
+			if !from.exists() {
+				continue;
+			}
+			let to = world.join(dir).join(format!("{new_id}.{ext}"));
+			if to.exists() {
+				report.conflicts.push(from);
+				continue;
+			}
+			fs::rename(&from, &to).with_context(|| format!("rename {} failed", from.display()))?;
+			report.moved.push(to);
+		}
+	}
+
+	Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_vanilla_uuid_matches_server() {
+		// 原版服务端离线模式下 Notch 的 UUID
+		assert_eq!(
+			vanilla_uuid("Notch").to_string(),
+			"b50ad385-829d-3141-a216-7e7d7539ba7f"
+		);
+	}
+
+	#[test]
+	fn test_migrate_playerdata() {
+		let dir = tempfile::tempdir().unwrap();
+		let old = legacy_uuid("Steve");
+		let new = vanilla_uuid("Steve");
+		let playerdata = dir.path().join("saves").join("World").join("playerdata");
+		fs::create_dir_all(&playerdata).unwrap();
+		fs::write(playerdata.join(format!("{old}.dat")), b"inv").unwrap();
+
+		let report = migrate_playerdata(dir.path(), old, new).unwrap();
+		assert_eq!(report.moved.len(), 1);
+		assert!(playerdata.join(format!("{new}.dat")).exists());
+		assert!(!playerdata.join(format!("{old}.dat")).exists());
+	}
+}
@@ -0,0 +1,386 @@
+use super::error::{AuthError, read_json};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::{ImageFormat, RgbaImage, imageops};
+use reqwest::header::{AUTHORIZATION, CONTENT_TYPE};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Response};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SkinModel {
+	#[default]
+	Classic,
+	Slim,
+}
+
+impl SkinModel {
+	fn from_variant(variant: &str) -> Self {
+		if variant.eq_ignore_ascii_case("slim") {
+			Self::Slim
+		} else {
+			Self::Classic
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Skin {
+	pub url: String,
+	pub model: SkinModel,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cape {
+	/// 正版披风 ID；Yggdrasil 披风没有 ID，为空
+	pub id: String,
+	pub alias: Option<String>,
+	pub active: bool,
+}
+
+/// 账户当前的外观
+#[derive(Debug, Clone, Default)]
+pub struct Appearance {
+	pub skin: Option<Skin>,
+	pub capes: Vec<Cape>,
+}
+
+impl Appearance {
+	pub fn active_cape(&self) -> Option<&Cape> {
+		self.capes.iter().find(|c| c.active)
+	}
+}
+
+#[derive(Deserialize)]
+struct ProfileResponse {
+	#[serde(default)]
+	skins: Vec<ProfileTexture>,
+	#[serde(default)]
+	capes: Vec<ProfileTexture>,
+}
+
+#[derive(Deserialize)]
+struct ProfileTexture {
+	#[serde(default)]
+	id: String,
+	state: String,
+	url: String,
+	#[serde(default)]
+	variant: Option<String>,
+	#[serde(default)]
+	alias: Option<String>,
+}
+
+impl From<ProfileResponse> for Appearance {
+	fn from(profile: ProfileResponse) -> Self {
+		let skin = profile
+			.skins
+			.into_iter()
+			.find(|s| s.state == "ACTIVE")
+			.map(|s| Skin {
+				url: s.url,
+				model: SkinModel::from_variant(s.variant.as_deref().unwrap_or_default()),
+			});
+		let capes = profile
+			.capes
+			.into_iter()
+			.map(|c| Cape {
+				active: c.state == "ACTIVE",
+				id: c.id,
+				alias: c.alias,
+			})
+			.collect();
+		Self { skin, capes }
+	}
+}
+
+#[derive(Deserialize)]
+struct SessionProfile {
+	#[serde(default)]
+	properties: Vec<SessionProperty>,
+}
+
+#[derive(Deserialize)]
+struct SessionProperty {
+	name: String,
+	value: String,
+}
+
+#[derive(Deserialize)]
+struct TexturesPayload {
+	#[serde(default)]
+	textures: HashMap<String, TexturePayload>,
+}
+
+#[derive(Deserialize)]
+struct TexturePayload {
+	url: String,
+	#[serde(default)]
+	metadata: HashMap<String, String>,
+}
+
+/// 正版与 Yggdrasil 的皮肤、披风接口
+pub struct SkinClient {
+	client: Client,
+}
+
+impl SkinClient {
+	pub fn new() -> Result<Self, AuthError> {
+		let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+		Ok(Self { client })
+	}
+
+	/// `api` 为 Minecraft 服务地址（`MicrosoftEndpoints::minecraft`）
+	pub async fn microsoft_appearance(
+		&self,
+		api: &str,
+		mc_token: &str,
+	) -> Result<Appearance, AuthError> {
+		let resp = self
+			.client
+			.get(format!("{api}/minecraft/profile"))
+			.header(AUTHORIZATION, format!("Bearer {mc_token}"))
+			.send()
+			.await?;
+		Ok(read_json::<ProfileResponse>(resp).await?.into())
+	}
+
+	pub async fn microsoft_upload_skin(
+		&self,
+		api: &str,
+		mc_token: &str,
+		png: Vec<u8>,
+		model: SkinModel,
+	) -> Result<Appearance, AuthError> {
+		let variant = match model {
+			SkinModel::Classic => "classic",
+			SkinModel::Slim => "slim",
+		};
+		let form = Form::new()
+			.text("variant", variant)
+			.part("file", skin_part(png)?);
+		let resp = self
+			.client
+			.post(format!("{api}/minecraft/profile/skins"))
+			.header(AUTHORIZATION, format!("Bearer {mc_token}"))
+			.multipart(form)
+			.send()
+			.await?;
+		Ok(read_json::<ProfileResponse>(resp).await?.into())
+	}
+
+	/// `cape_id` 为 None 时隐藏披风
+	pub async fn microsoft_select_cape(
+		&self,
+		api: &str,
+		mc_token: &str,
+		cape_id: Option<&str>,
+	) -> Result<Appearance, AuthError> {
+		let url = format!("{api}/minecraft/profile/capes/active");
+		let req = match cape_id {
+			Some(id) => self
+				.client
+				.put(url)
+				.header(CONTENT_TYPE, "application/json")
+				.body(serde_json::to_vec(&serde_json::json!({ "capeId": id }))?),
+			None => self.client.delete(url),
+		};
+		let resp = req
+			.header(AUTHORIZATION, format!("Bearer {mc_token}"))
+			.send()
+			.await?;
+		Ok(read_json::<ProfileResponse>(resp).await?.into())
+	}
+
+	/// 从会话服务器的 textures 属性读取外观
+	pub async fn yggdrasil_appearance(
+		&self,
+		server: &str,
+		uuid: &Uuid,
+	) -> Result<Appearance, AuthError> {
+		let resp = self
+			.client
+			.get(format!(
+				"{server}/sessionserver/session/minecraft/profile/{}",
+				uuid.simple()
+			))
+			.send()
+			.await?;
+		let profile: SessionProfile = read_json(resp).await?;
+		let Some(textures) = profile.properties.iter().find(|p| p.name == "textures") else {
+			return Ok(Appearance::default());
+		};
+		parse_textures(&textures.value)
+	}
+
+	pub async fn yggdrasil_upload_skin(
+		&self,
+		server: &str,
+		access_token: &str,
+		uuid: &Uuid,
+		png: Vec<u8>,
+		model: SkinModel,
+	) -> Result<(), AuthError> {
+		let model = match model {
+			SkinModel::Classic => "",
+			SkinModel::Slim => "slim",
+		};
+		let form = Form::new()
+			.text("model", model)
+			.part("file", skin_part(png)?);
+		let resp = self
+			.client
+			.put(format!("{server}/api/user/profile/{}/skin", uuid.simple()))
+			.header(AUTHORIZATION, format!("Bearer {access_token}"))
+			.multipart(form)
+			.send()
+			.await?;
+		expect_success(resp).await
+	}
+
+	pub async fn download_texture(&self, url: &str) -> Result<Vec<u8>, AuthError> {
+		let resp = self.client.get(url).send().await?;
+		let status = resp.status();
+		if !status.is_success() {
+			return Err(AuthError::UnexpectedStatus {
+				status,
+				body: resp.text().await?,
+			});
+		}
+		Ok(resp.bytes().await?.to_vec())
+	}
+}
+
+fn skin_part(png: Vec<u8>) -> Result<Part, AuthError> {
+	Ok(Part::bytes(png)
+		.file_name("skin.png")
+		.mime_str("image/png")?)
+}
+
+async fn expect_success(resp: Response) -> Result<(), AuthError> {
+	let status = resp.status();
+	if !status.is_success() {
+		return Err(AuthError::UnexpectedStatus {
+			status,
+			body: resp.text().await?,
+		});
+	}
+	Ok(())
+}
+
+fn parse_textures(value: &str) -> Result<Appearance, AuthError> {
+	let data = STANDARD
+		.decode(value)
+		.map_err(|e| AuthError::Server(format!("invalid textures property: {e}")))?;
+	let payload: TexturesPayload = serde_json::from_slice(&data)?;
+
+	let mut appearance = Appearance::default();
+	for (kind, texture) in payload.textures {
+		match kind.as_str() {
+			"SKIN" => {
+				appearance.skin = Some(Skin {
+					model: SkinModel::from_variant(
+						texture.metadata.get("model").map_or("", |m| m.as_str()),
+					),
+					url: texture.url,
+				})
+			}
+			"CAPE" => appearance.capes.push(Cape {
+				id: String::new(),
+				alias: None,
+				active: true,
+			}),
+			_ => {}
+		}
+	}
+	Ok(appearance)
+}
+
+/// 校验上传的皮肤：PNG 且为 64x64 或旧版 64x32
+pub fn validate_skin(png: &[u8]) -> Result<(), image::ImageError> {
+	let image = image::load_from_memory_with_format(png, ImageFormat::Png)?;
+	match (image.width(), image.height()) {
+		(64, 64) | (64, 32) => Ok(()),
+		(w, h) => Err(image::ImageError::Parameter(
+			image::error::ParameterError::from_kind(image::error::ParameterErrorKind::Generic(
+				format!("skin must be 64x64 or 64x32, got {w}x{h}"),
+			)),
+		)),
+	}
+}
+
+/// 从皮肤贴图裁出头像（脸部叠加帽子层），按最近邻放大到 `size`
+pub fn head_avatar(skin: &[u8], size: u32) -> Result<RgbaImage, image::ImageError> {
+	let skin = image::load_from_memory(skin)?.to_rgba8();
+	let mut head = imageops::crop_imm(&skin, 8, 8, 8, 8).to_image();
+	if skin.width() >= 48 {
+		let hat = imageops::crop_imm(&skin, 40, 8, 8, 8).to_image();
+		imageops::overlay(&mut head, &hat, 0, 0);
+	}
+	Ok(imageops::resize(
+		&head,
+		size,
+		size,
+		imageops::FilterType::Nearest,
+	))
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, image::ImageError> {
+	let mut out = Cursor::new(Vec::new());
+	image.write_to(&mut out, ImageFormat::Png)?;
+	Ok(out.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::serve;
+	use image::Rgba;
+
+	fn test_skin() -> Vec<u8> {
+		let mut skin = RgbaImage::new(64, 64);
+		for x in 8..16 {
+			for y in 8..16 {
+				skin.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+			}
+		}
+		// 帽子层只覆盖左上角
+		skin.put_pixel(40, 8, Rgba([0, 0, 255, 255]));
+		encode_png(&skin).unwrap()
+	}
+
+	#[test]
+	fn test_head_avatar() {
+		let skin = test_skin();
+		validate_skin(&skin).unwrap();
+
+		let head = head_avatar(&skin, 32).unwrap();
+		assert_eq!(head.dimensions(), (32, 32));
+		assert_eq!(head.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+		assert_eq!(head.get_pixel(31, 31), &Rgba([255, 0, 0, 255]));
+	}
+
+	#[tokio::test]
+	async fn test_microsoft_appearance() {
+		let url = serve(|req| {
+			assert!(req.contains("Bearer mc"));
+			let body = r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch",
+				"skins":[{"id":"s","state":"ACTIVE","url":"http://skin","variant":"SLIM"}],
+				"capes":[{"id":"c1","state":"INACTIVE","url":"http://c1","alias":"Migrator"},
+					{"id":"c2","state":"ACTIVE","url":"http://c2","alias":"Vanilla"}]}"#;
+			("200 OK", body.to_string())
+		})
+		.await;
+
+		let client = SkinClient::new().unwrap();
+		let appearance = client.microsoft_appearance(&url, "mc").await.unwrap();
+		let skin = appearance.skin.as_ref().unwrap();
+		assert_eq!(skin.model, SkinModel::Slim);
+		assert_eq!(appearance.capes.len(), 2);
+		assert_eq!(appearance.active_cape().unwrap().id, "c2");
+	}
+}
@@ -0,0 +1,433 @@
+use super::offline::OfflineUuidScheme;
+use super::{Account, AccountEntry, AccountId};
+use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// 账户文件的结构版本，字段变更时递增并在 `migrate` 中补充迁移
+pub const SCHEMA_VERSION: u32 = 2;
+
+const NONCE_LEN: usize = 12;
+
+/// 磁盘上的账户存储，令牌使用本机派生的密钥加密
+pub struct AccountStore {
+	path: PathBuf,
+	cipher: ChaCha20Poly1305,
+}
+
+#[derive(Debug, Default)]
+pub struct StoredState {
+	pub accounts: Vec<AccountEntry>,
+	pub current: Option<AccountId>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AccountsFile {
+	version: u32,
+	#[serde(default)]
+	current: Option<AccountId>,
+	#[serde(default)]
+	accounts: Vec<StoredEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredEntry {
+	id: AccountId,
+	#[serde(flatten)]
+	account: StoredAccount,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+enum StoredAccount {
+	Offline {
+		username: String,
+		uuid: Uuid,
+		#[serde(default)]
+		scheme: OfflineUuidScheme,
+	},
+	Microsoft {
+		username: String,
+		uuid: Uuid,
+		access_token: String,
+		#[serde(default)]
+		refresh_token: String,
+		#[serde(default)]
+		client_id: String,
+		#[serde(default)]
+		xuid: String,
+		#[serde(default)]
+		expires_at: u64,
+		#[serde(default)]
+		revoked: bool,
+		#[serde(default = "super::default_owns_game")]
+		owns_game: bool,
+	},
+	Yggdrasil {
+		username: String,
+		uuid: Uuid,
+		server: String,
+		access_token: String,
+		client_token: String,
+		#[serde(default)]
+		revoked: bool,
+	},
+}
+
+impl AccountStore {
+	pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+		let path = path.into();
+		let key_path = path.with_file_name("account.key");
+		let secret = load_or_create_secret(&key_path)?;
+		let key = derive_key(&secret);
+		Ok(Self {
+			path,
+			cipher: ChaCha20Poly1305::new(&key),
+		})
+	}
+
+	pub fn load(&self) -> Result<StoredState> {
+		if !self.path.exists() {
+			return Ok(StoredState::default());
+		}
+		let content = fs::read_to_string(&self.path).context("read accounts")?;
+		let value: serde_yaml::Value = serde_yaml::from_str(&content).context("parse accounts")?;
+		let file: AccountsFile =
+			serde_yaml::from_value(migrate(value)?).context("parse accounts")?;
+
+		let accounts = file
+			.accounts
+			.into_iter()
+			.map(|e| AccountEntry {
+				id: e.id,
+				account: self.open_account(e.id, e.account),
+			})
+			.collect();
+
+		Ok(StoredState {
+			accounts,
+			current: file.current,
+		})
+	}
+
+	pub fn save(&self, accounts: &[AccountEntry], current: Option<AccountId>) -> Result<()> {
+		let file = AccountsFile {
+			version: SCHEMA_VERSION,
+			current,
+			accounts: accounts
+				.iter()
+				.map(|e| {
+					Ok(StoredEntry {
+						id: e.id,
+						account: self.seal_account(&e.account)?,
+					})
+				})
+				.collect::<Result<_>>()?,
+		};
+
+		if let Some(dir) = self.path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		let yaml = serde_yaml::to_string(&file)?;
+		let tmp = self.path.with_extension("yml.tmp");
+		fs::write(&tmp, yaml)?;
+		fs::rename(&tmp, &self.path)?;
+		Ok(())
+	}
+
+	fn seal_account(&self, account: &Account) -> Result<StoredAccount> {
+		Ok(match account {
+			Account::Offline {
+				username,
+				uuid,
+				scheme,
+			} => StoredAccount::Offline {
+				username: username.clone(),
+				uuid: *uuid,
+				scheme: *scheme,
+			},
+			Account::Microsoft {
+				username,
+				uuid,
+				access_token,
+				refresh_token,
+				client_id,
+				xuid,
+				expires_at,
+				revoked,
+				owns_game,
+			} => StoredAccount::Microsoft {
+				username: username.clone(),
+				uuid: *uuid,
+				access_token: self.encrypt(access_token)?,
+				refresh_token: self.encrypt(refresh_token)?,
+				client_id: client_id.clone(),
+				xuid: xuid.clone(),
+				expires_at: *expires_at,
+				revoked: *revoked,
+				owns_game: *owns_game,
+			},
+			Account::Yggdrasil {
+				username,
+				uuid,
+				server,
+				access_token,
+				client_token,
+				revoked,
+			} => StoredAccount::Yggdrasil {
+				username: username.clone(),
+				uuid: *uuid,
+				server: server.clone(),
+				access_token: self.encrypt(access_token)?,
+				client_token: client_token.clone(),
+				revoked: *revoked,
+			},
+		})
+	}
+
+	fn open_account(&self, id: AccountId, account: StoredAccount) -> Account {
+		match account {
+			StoredAccount::Offline {
+				username,
+				uuid,
+				scheme,
+			} => Account::Offline {
+				username,
+				uuid,
+				scheme,
+			},
+			StoredAccount::Microsoft {
+				username,
+				uuid,
+				access_token,
+				refresh_token,
+				client_id,
+				xuid,
+				expires_at,
+				revoked,
+				owns_game,
+			} => Account::Microsoft {
+				access_token: self.decrypt_or_warn(id, &access_token),
+				refresh_token: self.decrypt_or_warn(id, &refresh_token),
+				username,
+				uuid,
+				client_id,
+				xuid,
+				expires_at,
+				revoked,
+				owns_game,
+			},
+			StoredAccount::Yggdrasil {
+				username,
+				uuid,
+				server,
+				access_token,
+				client_token,
+				revoked,
+			} => Account::Yggdrasil {
+				access_token: self.decrypt_or_warn(id, &access_token),
+				username,
+				uuid,
+				server,
+				client_token,
+				revoked,
+			},
+		}
+	}
+
+	fn encrypt(&self, plain: &str) -> Result<String> {
+		let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+		let data = self
+			.cipher
+			.encrypt(&nonce, plain.as_bytes())
+			.map_err(|_| anyhow::anyhow!("encrypt token failed"))?;
+		let mut out = nonce.to_vec();
+		out.extend(data);
+		Ok(hex::encode(out))
+	}
+
+	fn decrypt(&self, sealed: &str) -> Result<String> {
+		if sealed.is_empty() {
+			return Ok(String::new());
+		}
+		let raw = hex::decode(sealed).context("decode token")?;
+		if raw.len() < NONCE_LEN {
+			return Err(anyhow::anyhow!("token too short"));
+		}
+		let (nonce, data) = raw.split_at(NONCE_LEN);
+		let plain = self
+			.cipher
+			.decrypt(Nonce::from_slice(nonce), data)
+			.map_err(|_| anyhow::anyhow!("decrypt token failed"))?;
+		String::from_utf8(plain).context("token is not utf-8")
+	}
+
+	// 密钥变化（换机、key 文件丢失）时令牌无法恢复，只能要求重新登录
+	fn decrypt_or_warn(&self, id: AccountId, sealed: &str) -> String {
+		self.decrypt(sealed).unwrap_or_else(|e| {
+			tracing::warn!("Account {} token unreadable, re-login required: {}", id, e);
+			String::new()
+		})
+	}
+}
+
+fn migrate(mut value: serde_yaml::Value) -> Result<serde_yaml::Value> {
+	let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+
+	if version > SCHEMA_VERSION {
+		return Err(anyhow::anyhow!(
+			"accounts file version {version} is newer than supported {SCHEMA_VERSION}"
+		));
+	}
+
+	// v1 -> v2：未记录生成方式的离线账户来自旧版本，使用的是 v5 UUID
+	if version < 2
+		&& let Some(accounts) = value.get_mut("accounts").and_then(|a| a.as_sequence_mut())
+	{
+		for account in accounts.iter_mut().filter_map(|a| a.as_mapping_mut()) {
+			if account.get("type").and_then(|t| t.as_str()) == Some("Offline")
+				&& !account.contains_key("scheme")
+			{
+				account.insert("scheme".into(), "Legacy".into());
+			}
+		}
+	}
+
+	if let Some(map) = value.as_mapping_mut() {
+		map.insert("version".into(), SCHEMA_VERSION.into());
+	}
+	Ok(value)
+}
+
+fn load_or_create_secret(path: &Path) -> Result<Vec<u8>> {
+	if let Ok(secret) = fs::read(path)
+		&& !secret.is_empty()
+	{
+		return Ok(secret);
+	}
+
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir)?;
+	}
+	let secret = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+	write_private(path, &secret).context("write account key")?;
+	Ok(secret)
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+	use std::io::Write;
+	use std::os::unix::fs::OpenOptionsExt;
+	fs::OpenOptions::new()
+		.create(true)
+		.write(true)
+		.truncate(true)
+		.mode(0o600)
+		.open(path)?
+		.write_all(data)
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+	fs::write(path, data)
+}
+
+fn derive_key(secret: &[u8]) -> Key {
+	let mut hasher = Sha256::new();
+	hasher.update(b"hako-accounts-v1");
+	hasher.update(machine_id().as_bytes());
+	hasher.update(secret);
+	hasher.finalize()
+}
+
+fn machine_id() -> String {
+	["/etc/machine-id", "/var/lib/dbus/machine-id"]
+		.iter()
+		.find_map(|p| fs::read_to_string(p).ok())
+		.map(|s| s.trim().to_string())
+		.or_else(|| std::env::var("COMPUTERNAME").ok())
+		.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_round_trip_encrypts_tokens() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("accounts.yml");
+		let store = AccountStore::open(&path).unwrap();
+
+		let offline = AccountEntry {
+			id: Uuid::new_v4(),
+			account: Account::offline("Steve"),
+		};
+		let microsoft = AccountEntry {
+			id: Uuid::new_v4(),
+			account: Account::Microsoft {
+				username: "Alex".into(),
+				uuid: Uuid::new_v4(),
+				access_token: "secret-token".into(),
+				refresh_token: "refresh-token".into(),
+				client_id: "client".into(),
+				xuid: "2535".into(),
+				expires_at: 0,
+				revoked: false,
+				owns_game: true,
+			},
+		};
+		store
+			.save(&[offline.clone(), microsoft.clone()], Some(microsoft.id))
+			.unwrap();
+
+		let raw = fs::read_to_string(&path).unwrap();
+		assert!(!raw.contains("secret-token"));
+		assert!(!raw.contains("refresh-token"));
+		assert!(raw.contains("version: 2"));
+
+		let loaded = AccountStore::open(&path).unwrap().load().unwrap();
+		assert_eq!(loaded.current, Some(microsoft.id));
+		assert_eq!(loaded.accounts.len(), 2);
+		assert_eq!(loaded.accounts[0].id, offline.id);
+		assert_eq!(
+			loaded.accounts[1].account.access_token(),
+			Some("secret-token")
+		);
+	}
+
+	#[test]
+	fn test_migrate_marks_legacy_offline() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().join("accounts.yml");
+		let (old, new) = (Uuid::new_v4(), Uuid::new_v4());
+		fs::write(
+			&path,
+			format!(
+				"version: 1\naccounts:\n\
+				- id: {old}\n  type: Offline\n  username: Steve\n  uuid: {old}\n\
+				- id: {new}\n  type: Offline\n  username: Alex\n  uuid: {new}\n  scheme: Vanilla\n"
+			),
+		)
+		.unwrap();
+
+		let loaded = AccountStore::open(&path).unwrap().load().unwrap();
+		let schemes: Vec<_> = loaded
+			.accounts
+			.iter()
+			.map(|e| match e.account {
+				Account::Offline { scheme, .. } => scheme,
+				_ => unreachable!(),
+			})
+			.collect();
+		assert_eq!(
+			schemes,
+			[OfflineUuidScheme::Legacy, OfflineUuidScheme::Vanilla]
+		);
+	}
+}
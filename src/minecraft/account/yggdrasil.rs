@@ -0,0 +1,179 @@
+use super::error::{AuthError, read_json};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+use uuid::Uuid;
+
+const ALI_HEADER: &str = "x-authlib-injector-api-location";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct YggdrasilProfile {
+	pub id: String,
+	pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YggdrasilSession {
+	pub access_token: String,
+	pub client_token: String,
+	#[serde(default)]
+	pub available_profiles: Vec<YggdrasilProfile>,
+	#[serde(default)]
+	pub selected_profile: Option<YggdrasilProfile>,
+}
+
+impl YggdrasilSession {
+	/// 服务器未选定角色时取第一个可用角色
+	pub fn profile(&self) -> Option<&YggdrasilProfile> {
+		self.selected_profile
+			.as_ref()
+			.or_else(|| self.available_profiles.first())
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct YggdrasilError {
+	#[serde(default)]
+	error: String,
+	#[serde(default)]
+	error_message: Option<String>,
+}
+
+/// Yggdrasil 兼容的第三方认证服务器（authlib-injector 规范）
+pub struct YggdrasilClient {
+	client: Client,
+	server: String,
+}
+
+impl YggdrasilClient {
+	pub fn new(server: impl Into<String>) -> Result<Self, AuthError> {
+		let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+		Ok(Self {
+			client,
+			server: server.into().trim_end_matches('/').to_string(),
+		})
+	}
+
+	pub fn server(&self) -> &str {
+		&self.server
+	}
+
+	/// 按 API 地址指示（ALI）解析用户输入的地址，得到真正的 API 根
+	pub async fn resolve(input: &str) -> Result<Self, AuthError> {
+		let mut url = input.trim().to_string();
+		if !url.starts_with("http://") && !url.starts_with("https://") {
+			url = format!("https://{url}");
+		}
+
+		let probe = Self::new(&url)?;
+		let resp = probe.client.get(&url).send().await?;
+		let api_root = match resp.headers().get(ALI_HEADER).and_then(|v| v.to_str().ok()) {
+			Some(location) => resp
+				.url()
+				.join(location)
+				.map(|u| u.to_string())
+				.unwrap_or_else(|_| location.to_string()),
+			None => url,
+		};
+		Self::new(api_root)
+	}
+
+	pub async fn authenticate(
+		&self,
+		username: &str,
+		password: &str,
+		client_token: &str,
+	) -> Result<YggdrasilSession, AuthError> {
+		let body = serde_json::json!({
+			"agent": { "name": "Minecraft", "version": 1 },
+			"username": username,
+			"password": password,
+			"clientToken": client_token,
+			"requestUser": false,
+		});
+		let resp = self.post("authserver/authenticate", &body).await?;
+		parse_response(resp).await
+	}
+
+	pub async fn refresh(
+		&self,
+		access_token: &str,
+		client_token: &str,
+		profile: Option<&YggdrasilProfile>,
+	) -> Result<YggdrasilSession, AuthError> {
+		let mut body = serde_json::json!({
+			"accessToken": access_token,
+			"clientToken": client_token,
+		});
+		if let Some(p) = profile {
+			body["selectedProfile"] = serde_json::json!({ "id": p.id, "name": p.name });
+		}
+		let resp = self.post("authserver/refresh", &body).await?;
+		if resp.status() == StatusCode::FORBIDDEN {
+			return Err(AuthError::Revoked);
+		}
+		parse_response(resp).await
+	}
+
+	/// 令牌有效返回 true，无效（403）返回 false
+	pub async fn validate(
+		&self,
+		access_token: &str,
+		client_token: &str,
+	) -> Result<bool, AuthError> {
+		let body = serde_json::json!({
+			"accessToken": access_token,
+			"clientToken": client_token,
+		});
+		let resp = self.post("authserver/validate", &body).await?;
+		match resp.status() {
+			s if s.is_success() => Ok(true),
+			StatusCode::FORBIDDEN => Ok(false),
+			status => Err(AuthError::UnexpectedStatus {
+				status,
+				body: resp.text().await?,
+			}),
+		}
+	}
+
+	/// 服务器元数据原文，用于 authlib-injector 预取
+	pub async fn metadata(&self) -> Result<String, AuthError> {
+		let resp = self.client.get(&self.server).send().await?;
+		let status = resp.status();
+		let body = resp.text().await?;
+		if !status.is_success() {
+			return Err(AuthError::UnexpectedStatus { status, body });
+		}
+		Ok(body)
+	}
+
+	async fn post(&self, path: &str, body: &serde_json::Value) -> Result<Response, AuthError> {
+		Ok(self
+			.client
+			.post(format!("{}/{path}", self.server))
+			.header(CONTENT_TYPE, "application/json")
+			.body(serde_json::to_vec(body)?)
+			.send()
+			.await?)
+	}
+}
+
+async fn parse_response<T: DeserializeOwned>(resp: Response) -> Result<T, AuthError> {
+	if resp.status().is_client_error() {
+		let status = resp.status();
+		let body = resp.text().await?;
+		return Err(match serde_json::from_str::<YggdrasilError>(&body) {
+			Ok(e) => AuthError::Server(e.error_message.unwrap_or(e.error)),
+			Err(_) => AuthError::UnexpectedStatus { status, body },
+		});
+	}
+	read_json(resp).await
+}
+
+pub fn parse_profile_id(id: &str) -> Result<Uuid, AuthError> {
+	Uuid::parse_str(id).map_err(|e| AuthError::Server(format!("invalid profile id {id}: {e}")))
+}
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdoptiumRelease {
+	pub binary: AdoptiumBinary,
+	pub release_name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdoptiumBinary {
+	pub package: AdoptiumPackage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdoptiumPackage {
+	pub name: String,
+	pub link: String,
+	/// SHA-256
+	pub checksum: String,
+	pub size: u64,
+}
+
+/// Adoptium API 使用的系统名
+pub fn adoptium_os() -> &'static str {
+	match std::env::consts::OS {
+		"macos" => "mac",
+		other => other,
+	}
+}
+
+/// Adoptium API 使用的架构名
+pub fn adoptium_arch() -> &'static str {
+	match std::env::consts::ARCH {
+		"x86_64" => "x64",
+		"x86" => "x86",
+		"aarch64" => "aarch64",
+		"arm" => "arm",
+		"powerpc64" => "ppc64le",
+		"s390x" => "s390x",
+		"riscv64" => "riscv64",
+		other => other,
+	}
+}
+
+/// 查询指定主版本在本机系统与架构下的最新 Temurin JDK
+pub async fn latest_release(api: &str, major: u32) -> Result<AdoptiumRelease> {
+	let url = format!(
+		"{}/v3/assets/latest/{major}/hotspot?image_type=jdk&vendor=eclipse&os={}&architecture={}",
+		api.trim_end_matches('/'),
+		adoptium_os(),
+		adoptium_arch()
+	);
+	let resp = reqwest::get(&url).await.context("query adoptium")?;
+	let status = resp.status();
+	let text = resp.text().await.context("read adoptium response")?;
+	if !status.is_success() {
+		return Err(anyhow::anyhow!("adoptium returned {status}: {text}"));
+	}
+	let releases: Vec<AdoptiumRelease> =
+		serde_json::from_str(&text).context("parse adoptium response")?;
+	releases.into_iter().next().ok_or_else(|| {
+		anyhow::anyhow!(
+			"no Temurin {major} build for {}-{}",
+			adoptium_os(),
+			adoptium_arch()
+		)
+	})
+}
+
+/// 解压 JDK 压缩包（tar.gz 或 zip）到 `dest`，先解到临时目录再替换，避免留下半个 JDK
+pub fn unpack_archive(archive: &Path, dest: &Path) -> Result<()> {
+	let staging = dest.with_extension("hako.part");
+	if staging.exists() {
+		fs::remove_dir_all(&staging)?;
+	}
+	fs::create_dir_all(&staging)?;
+
+	let name = archive.to_string_lossy();
+	if name.ends_with(".zip") {
+		let file = fs::File::open(archive)?;
+		zip::ZipArchive::new(file)
+			.context("open jdk zip")?
+			.extract(&staging)
+			.context("extract jdk zip")?;
+	} else {
+		let file = fs::File::open(archive)?;
+		let mut tar = tar::Archive::new(GzDecoder::new(file));
+		tar.set_preserve_permissions(true);
+		tar.unpack(&staging).context("extract jdk archive")?;
+	}
+
+	if dest.exists() {
+		fs::remove_dir_all(dest)?;
+	}
+	fs::rename(&staging, dest)?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::serve;
+	use flate2::Compression;
+	use flate2::write::GzEncoder;
+
+	#[tokio::test]
+	async fn test_latest_release() {
+		let url = serve(|req| {
+			assert!(req.starts_with("GET /v3/assets/latest/17/hotspot?"));
+			let body = r#"[{"binary":{"package":{"name":"OpenJDK17U-jdk.tar.gz","link":"http://jdk","checksum":"abc","size":1}},"release_name":"jdk-17.0.9+9"}]"#;
+			("200 OK", body.to_string())
+		})
+		.await;
+
+		let release = latest_release(&url, 17).await.unwrap();
+		assert_eq!(release.release_name, "jdk-17.0.9+9");
+		assert_eq!(release.binary.package.checksum, "abc");
+	}
+
+	#[test]
+	fn test_unpack_tar_gz() {
+		let dir = tempfile::tempdir().unwrap();
+		let archive = dir.path().join("jdk.tar.gz");
+
+		let mut builder = tar::Builder::new(GzEncoder::new(
+			fs::File::create(&archive).unwrap(),
+			Compression::default(),
+		));
+		let data = b"JAVA_VERSION=\"17.0.9\"\n";
+		let mut header = tar::Header::new_gnu();
+		header.set_size(data.len() as u64);
+		header.set_mode(0o644);
+		header.set_cksum();
+		builder
+			.append_data(&mut header, "jdk-17.0.9+9/release", &data[..])
+			.unwrap();
+		builder.into_inner().unwrap().finish().unwrap();
+
+		let dest = dir.path().join("temurin-17");
+		unpack_archive(&archive, &dest).unwrap();
+		assert!(dest.join("jdk-17.0.9+9").join("release").exists());
+	}
+}
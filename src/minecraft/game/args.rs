@@ -1,3 +1,4 @@
+use crate::minecraft::account::Account;
 use crate::minecraft::profile::{ArgValueInner, ArgumentValue, Rule, RuleOs, VersionProfile};
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -8,7 +9,6 @@ static TEMPLATE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{([^}]+)\}").unwr
 
 #[derive(Debug, Clone, Default)]
 pub struct Features {
-	#[allow(dead_code)]
 	pub is_demo_user: bool,
 	#[allow(dead_code)]
 	pub has_custom_resolution: bool,
@@ -22,26 +22,22 @@ pub struct Features {
 	pub is_quick_play_realms: bool,
 }
 
+/// 填充参数模板所需的启动信息
+pub struct LaunchContext<'a> {
+	pub game_dir: &'a Path,
+	pub version: &'a str,
+	pub assets_index: &'a str,
+	pub account: &'a Account,
+	pub features: &'a Features,
+}
+
 pub fn collect_jvm_args(
 	profile: &VersionProfile,
-	game_dir: &Path,
-	version: &str,
+	launch: &LaunchContext,
 	classpath: &str,
-	assets_index: &str,
-	username: &str,
-	uuid: &str,
 	natives_dir: &Path,
-	features: &Features,
 ) -> Vec<String> {
-	let mut replacements = build_replacements(
-		game_dir,
-		version,
-		assets_index,
-		username,
-		uuid,
-		Some(natives_dir),
-		Some(classpath),
-	);
+	let mut replacements = build_replacements(launch, Some(natives_dir), Some(classpath));
 	replacements.insert("${launcher_name}".to_string(), "Hako".to_string());
 	replacements.insert(
 		"${launcher_version}".to_string(),
@@ -49,30 +45,36 @@ pub fn collect_jvm_args(
 	);
 	replacements.insert(
 		"${library_directory}".to_string(),
-		game_dir.join("libraries").to_string_lossy().into_owned(),
+		launch
+			.game_dir
+			.join("libraries")
+			.to_string_lossy()
+			.into_owned(),
 	);
 	replacements.insert(
 		"${classpath_separator}".to_string(),
 		if cfg!(windows) { ";" } else { ":" }.to_string(),
 	);
-	collect_args(profile, true, &replacements, features)
+	collect_args(profile, true, &replacements, launch.features)
 }
 
-pub fn collect_game_args(
-	game_dir: &Path,
-	version: &str,
-	profile: &VersionProfile,
-	username: &str,
-	uuid: &str,
-	assets_index: &str,
-	features: &Features,
-) -> Vec<String> {
-	let mut replacements =
-		build_replacements(game_dir, version, assets_index, username, uuid, None, None);
+pub fn collect_game_args(profile: &VersionProfile, launch: &LaunchContext) -> Vec<String> {
+	let LaunchContext {
+		game_dir,
+		version,
+		assets_index,
+		account,
+		features,
+	} = *launch;
+	let mut replacements = build_replacements(launch, None, None);
+	let access_token = launch_access_token(account);
+	let user_type = user_type(account);
+	let username = account.username();
+	let uuid = account.uuid().simple().to_string();
 	replacements.insert("${version}".to_string(), version.to_string());
 	replacements.insert("${assetIndex}".to_string(), assets_index.to_string());
-	replacements.insert("${accessToken}".to_string(), "0".to_string());
-	replacements.insert("${userType}".to_string(), "mojang".to_string());
+	replacements.insert("${accessToken}".to_string(), access_token.to_string());
+	replacements.insert("${userType}".to_string(), user_type.to_string());
 
 	if profile.arguments.is_some() {
 		collect_args(profile, false, &replacements, features)
@@ -86,7 +88,7 @@ pub fn collect_game_args(
 			"--username".into(),
 			username.into(),
 			"--uuid".into(),
-			uuid.into(),
+			uuid,
 			"--version".into(),
 			version.into(),
 			"--gameDir".into(),
@@ -96,9 +98,9 @@ pub fn collect_game_args(
 			"--assetIndex".into(),
 			assets_index.into(),
 			"--accessToken".into(),
-			"0".into(),
+			access_token.to_string(),
 			"--userType".into(),
-			"mojang".into(),
+			user_type.to_string(),
 		]);
 		out
 	} else {
@@ -107,15 +109,21 @@ pub fn collect_game_args(
 }
 
 fn build_replacements(
-	game_dir: &Path,
-	version: &str,
-	assets_index: &str,
-	username: &str,
-	uuid: &str,
+	launch: &LaunchContext,
 	natives_dir: Option<&Path>,
 	classpath: Option<&str>,
 ) -> HashMap<String, String> {
+	let LaunchContext {
+		game_dir,
+		version,
+		assets_index,
+		account,
+		..
+	} = *launch;
 	let assets_dir = game_dir.join("assets");
+	let username = account.username();
+	let uuid = account.uuid().simple().to_string();
+	let access_token = launch_access_token(account);
 	let mut replacements = HashMap::new();
 
 	replacements.insert("${version_name}".to_string(), version.to_string());
@@ -145,9 +153,19 @@ fn build_replacements(
 	);
 	replacements.insert("${assetIndex}".to_string(), assets_index.to_string());
 	replacements.insert("${assets_index_name}".to_string(), assets_index.to_string());
-	replacements.insert("${auth_access_token}".to_string(), "0".to_string());
-	replacements.insert("${auth_session}".to_string(), "0".to_string());
-	replacements.insert("${user_type}".to_string(), "mojang".to_string());
+	replacements.insert("${auth_access_token}".to_string(), access_token.to_string());
+	replacements.insert(
+		"${auth_session}".to_string(),
+		if account.is_offline() {
+			access_token.to_string()
+		} else {
+			format!("token:{access_token}:{uuid}")
+		},
+	);
+	replacements.insert("${user_type}".to_string(), user_type(account).to_string());
+	replacements.insert("${user_properties}".to_string(), "{}".to_string());
+	replacements.insert("${clientid}".to_string(), client_id(account).to_string());
+	replacements.insert("${auth_xuid}".to_string(), xuid(account).to_string());
 
 	if let Some(natives_dir) = natives_dir {
 		replacements.insert(
@@ -162,6 +180,38 @@ fn build_replacements(
 	replacements
 }
 
+// 参数按空白切分，空值会让后面的参数错位，缺失的值统一用 "0" 占位
+fn launch_access_token(account: &Account) -> &str {
+	or_placeholder(account.access_token().unwrap_or_default())
+}
+
+fn user_type(account: &Account) -> &'static str {
+	match account {
+		Account::Microsoft { .. } => "msa",
+		// authlib-injector 沿用 Mojang 旧账户体系
+		Account::Yggdrasil { .. } => "mojang",
+		Account::Offline { .. } => "legacy",
+	}
+}
+
+fn client_id(account: &Account) -> &str {
+	match account {
+		Account::Microsoft { client_id, .. } => or_placeholder(client_id),
+		Account::Offline { .. } | Account::Yggdrasil { .. } => "0",
+	}
+}
+
+fn xuid(account: &Account) -> &str {
+	match account {
+		Account::Microsoft { xuid, .. } => or_placeholder(xuid),
+		Account::Offline { .. } | Account::Yggdrasil { .. } => "0",
+	}
+}
+
+fn or_placeholder(value: &str) -> &str {
+	if value.is_empty() { "0" } else { value }
+}
+
 fn collect_args(
 	profile: &VersionProfile,
 	is_jvm: bool,
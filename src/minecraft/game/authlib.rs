@@ -0,0 +1,102 @@
+use crate::infrastructure::network::download::{Checksum, DownloadClient, DownloadRequest};
+use crate::launcher::core::paths;
+use crate::minecraft::account::yggdrasil::YggdrasilClient;
+use anyhow::{Context, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
+
+pub const AUTHLIB_INJECTOR_LATEST: &str = "https://authlib-injector.yushi.moe/artifact/latest.json";
+
+#[derive(Deserialize)]
+struct ArtifactInfo {
+	version: String,
+	download_url: String,
+	#[serde(default)]
+	checksums: ArtifactChecksums,
+}
+
+#[derive(Deserialize, Default)]
+struct ArtifactChecksums {
+	#[serde(default)]
+	sha256: Option<String>,
+}
+
+/// 为 Yggdrasil 账户生成 authlib-injector 的 JVM 参数，并预取服务器元数据
+pub async fn authlib_injector_args(
+	server: &str,
+	latest_url: &str,
+	cancel: Option<watch::Receiver<bool>>,
+) -> Result<Vec<String>> {
+	let dir = paths::data_dir()?.join("authlib-injector");
+	let jar = ensure_authlib_injector(&dir, latest_url, cancel).await?;
+
+	let mut args = vec![
+		format!("-javaagent:{}={}", jar.display(), server),
+		"-Dauthlibinjector.side=client".to_string(),
+	];
+
+	// 预取失败不影响启动，authlib-injector 会自行请求
+	match YggdrasilClient::new(server)?.metadata().await {
+		Ok(meta) => args.push(format!(
+			"-Dauthlibinjector.yggdrasil.prefetched={}",
+			STANDARD.encode(meta)
+		)),
+		Err(e) => tracing::warn!("Prefetch yggdrasil metadata failed: {}", e),
+	}
+
+	Ok(args)
+}
+
+pub async fn ensure_authlib_injector(
+	dir: &Path,
+	latest_url: &str,
+	cancel: Option<watch::Receiver<bool>>,
+) -> Result<PathBuf> {
+	let info = match fetch_latest(latest_url).await {
+		Ok(info) => info,
+		Err(e) => {
+			// 离线时使用已下载的版本
+			return newest_local(dir).with_context(|| format!("authlib-injector unavailable: {e}"));
+		}
+	};
+
+	let dest = dir.join(format!("authlib-injector-{}.jar", info.version));
+	let mut request = DownloadRequest::new(info.download_url, &dest);
+	if let Some(sha256) = info.checksums.sha256 {
+		request = request.with_checksum(Checksum::Sha256(sha256));
+	} else if dest.exists() {
+		return Ok(dest);
+	}
+
+	DownloadClient::new()?
+		.download(request, |_| {}, cancel)
+		.await
+		.context("download authlib-injector")?;
+	Ok(dest)
+}
+
+async fn fetch_latest(url: &str) -> Result<ArtifactInfo> {
+	let text = reqwest::get(url)
+		.await
+		.context("fetch authlib-injector metadata")?
+		.error_for_status()?
+		.text()
+		.await?;
+	serde_json::from_str(&text).context("parse authlib-injector metadata")
+}
+
+fn newest_local(dir: &Path) -> Result<PathBuf> {
+	std::fs::read_dir(dir)?
+		.flatten()
+		.map(|e| e.path())
+		.filter(|p| {
+			p.file_name()
+				.and_then(|n| n.to_str())
+				.is_some_and(|n| n.starts_with("authlib-injector-") && n.ends_with(".jar"))
+		})
+		.max_by_key(|p| p.metadata().and_then(|m| m.modified()).ok())
+		.context("no local authlib-injector")
+}
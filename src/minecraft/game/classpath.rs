@@ -1,4 +1,5 @@
 use crate::minecraft::game::args::{Features, current_arch, current_os_key, rule_allows};
+use crate::minecraft::game::maven::MavenCoord;
 use crate::minecraft::profile::{Library, VersionProfile};
 use anyhow::Result;
 use std::collections::HashSet;
@@ -10,10 +11,11 @@ pub fn build_classpath(
 	profile: &VersionProfile,
 	features: &Features,
 ) -> Result<String> {
+	let jar_id = profile.jar_id(version);
 	let version_jar = game_dir
 		.join("versions")
-		.join(version)
-		.join(format!("{version}.jar"));
+		.join(jar_id)
+		.join(format!("{jar_id}.jar"));
 	if !version_jar.exists() {
 		return Err(anyhow::anyhow!(
 			"Version jar missing: {}",
@@ -51,27 +53,9 @@ pub fn build_classpath(
 }
 
 fn maven_path(game_dir: &Path, coord: &str, classifier: Option<&str>) -> Result<PathBuf> {
-	let parts: Vec<&str> = coord.split(':').collect();
-	if parts.len() < 3 {
-		return Err(anyhow::anyhow!("Invalid maven coord: {coord}"));
-	}
-	let group = parts[0].replace('.', "/");
-	let artifact = parts[1];
-	let version = parts[2];
-	let classifier = classifier.or_else(|| parts.get(3).copied());
-
-	let file_name = if let Some(c) = classifier {
-		format!("{artifact}-{version}-{c}.jar")
-	} else {
-		format!("{artifact}-{version}.jar")
-	};
-
-	Ok(game_dir
-		.join("libraries")
-		.join(group)
-		.join(artifact)
-		.join(version)
-		.join(file_name))
+	Ok(MavenCoord::parse(coord)?
+		.with_classifier(classifier)
+		.local_path(&game_dir.join("libraries")))
 }
 
 pub fn library_applicable(lib: &Library, os_key: &str, arch: &str, features: &Features) -> bool {
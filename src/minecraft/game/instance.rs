@@ -1,12 +1,130 @@
+use crate::minecraft::loader::LoaderInfo;
+use crate::minecraft::loader::detect::detect_instance;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 #[derive(Debug, Clone)]
 pub struct GameInstance {
 	pub cluster_path: PathBuf,
 	pub version: String,
 	pub version_path: PathBuf,
+	/// 原版游戏版本，版本 JSON 无法解析时为 None
+	pub game_version: Option<String>,
+	/// release / snapshot / old_beta / old_alpha
+	pub release_type: Option<String>,
+	pub loader: Option<LoaderInfo>,
+	pub java_major: Option<u32>,
+	/// 版本 JSON 的修改时间
+	pub modified: Option<SystemTime>,
+	pub issues: Vec<HealthIssue>,
+}
+
+/// 扫描时发现的实例问题，实例仍会列出以便用户处理
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthIssue {
+	/// 版本 JSON 或其 inheritsFrom 链无法读取、解析
+	BrokenProfile(String),
+	/// 客户端 jar 不存在
+	MissingClientJar(String),
+}
+
+impl fmt::Display for HealthIssue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			HealthIssue::BrokenProfile(e) => write!(f, "版本 JSON 损坏：{e}"),
+			HealthIssue::MissingClientJar(jar) => write!(f, "缺少客户端 {jar}.jar"),
+		}
+	}
+}
+
+impl GameInstance {
+	/// 解析 `versions/<version>` 的版本 JSON，填充游戏版本、加载器等信息
+	pub fn load(cluster_path: &Path, version: &str) -> Self {
+		let version_path = cluster_path.join("versions").join(version);
+		let modified = fs::metadata(version_path.join(format!("{version}.json")))
+			.and_then(|m| m.modified())
+			.ok();
+		let mut instance = Self {
+			cluster_path: cluster_path.to_path_buf(),
+			version: version.to_string(),
+			version_path,
+			game_version: None,
+			release_type: None,
+			loader: None,
+			java_major: None,
+			modified,
+			issues: Vec::new(),
+		};
+		match detect_instance(cluster_path, version) {
+			Ok(detected) => {
+				let jar_id = detected.profile.jar_id(version);
+				if !cluster_path
+					.join("versions")
+					.join(jar_id)
+					.join(format!("{jar_id}.jar"))
+					.exists()
+				{
+					instance
+						.issues
+						.push(HealthIssue::MissingClientJar(jar_id.to_string()));
+				}
+				instance.java_major = Some(detected.profile.required_java());
+				instance.release_type = detected.profile.version_type;
+				instance.game_version = Some(detected.game_version);
+				instance.loader = detected.loader;
+			}
+			Err(e) => {
+				tracing::warn!("Broken version profile {version}: {e:#}");
+				instance
+					.issues
+					.push(HealthIssue::BrokenProfile(format!("{e:#}")));
+			}
+		}
+		instance
+	}
+
+	pub fn is_healthy(&self) -> bool {
+		self.issues.is_empty()
+	}
+}
+
+/// Hako 记录的实例信息，保存在 `versions/<id>/Hako/instance.yml`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceMeta {
+	/// 原版游戏版本
+	pub game_version: Option<String>,
+	pub loader: Option<LoaderInfo>,
+}
+
+impl InstanceMeta {
+	pub fn path(cluster_path: &Path, version: &str) -> PathBuf {
+		cluster_path
+			.join("versions")
+			.join(version)
+			.join("Hako")
+			.join("instance.yml")
+	}
+
+	pub fn load(cluster_path: &Path, version: &str) -> Self {
+		fs::read_to_string(Self::path(cluster_path, version))
+			.ok()
+			.and_then(|s| serde_yaml::from_str(&s).ok())
+			.unwrap_or_default()
+	}
+
+	pub fn save(&self, cluster_path: &Path, version: &str) -> Result<()> {
+		let path = Self::path(cluster_path, version);
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir)?;
+		}
+		fs::write(path, serde_yaml::to_string(self)?)?;
+		Ok(())
+	}
 }
 
 pub struct InstanceScanner;
@@ -39,11 +157,7 @@ impl InstanceScanner {
 			if let Some(version) = version_name {
 				let json_path = path.join(format!("{}.json", version));
 				if json_path.exists() {
-					instances.push(GameInstance {
-						cluster_path: cluster_path.to_path_buf(),
-						version,
-						version_path: path,
-					});
+					instances.push(GameInstance::load(cluster_path, &version));
 				}
 			}
 		}
@@ -51,6 +165,7 @@ impl InstanceScanner {
 		Ok(instances)
 	}
 
+	/// 扫描多个游戏目录，最近修改过的实例排在前面
 	pub fn scan_clusters(cluster_paths: &[PathBuf]) -> Result<Vec<GameInstance>> {
 		let mut all_instances = Vec::new();
 
@@ -62,7 +177,85 @@ impl InstanceScanner {
 				}
 			}
 		}
+		all_instances.sort_by_key(|i| std::cmp::Reverse(i.modified));
 
 		Ok(all_instances)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::minecraft::loader::LoaderKind;
+
+	fn write(dir: &Path, version: &str, file: &str, content: &str) {
+		let dir = dir.join("versions").join(version);
+		fs::create_dir_all(&dir).unwrap();
+		fs::write(dir.join(file), content).unwrap();
+	}
+
+	#[test]
+	fn test_scan_metadata() {
+		let dir = tempfile::tempdir().unwrap();
+		write(
+			dir.path(),
+			"1.20.1",
+			"1.20.1.json",
+			r#"{"id":"1.20.1","type":"release","javaVersion":{"component":"java-runtime-gamma","majorVersion":17}}"#,
+		);
+		write(dir.path(), "1.20.1", "1.20.1.jar", "");
+		write(
+			dir.path(),
+			"fabric",
+			"fabric.json",
+			r#"{"id":"fabric","inheritsFrom":"1.20.1","jar":"1.20.1","libraries":[{"name":"net.fabricmc:fabric-loader:0.15.0"}]}"#,
+		);
+		write(
+			dir.path(),
+			"broken",
+			"broken.json",
+			r#"{"id":"broken","inheritsFrom":"1.8.9"}"#,
+		);
+
+		let mut instances = InstanceScanner::scan_cluster(dir.path()).unwrap();
+		instances.sort_by(|a, b| a.version.cmp(&b.version));
+		assert_eq!(instances.len(), 3);
+
+		let fabric = &instances[2];
+		assert_eq!(fabric.game_version.as_deref(), Some("1.20.1"));
+		assert_eq!(fabric.release_type.as_deref(), Some("release"));
+		assert_eq!(fabric.java_major, Some(17));
+		assert_eq!(
+			fabric.loader.as_ref().map(|l| l.kind),
+			Some(LoaderKind::Fabric)
+		);
+		assert!(fabric.is_healthy() && fabric.modified.is_some());
+
+		let broken = &instances[1];
+		assert!(broken.game_version.is_none());
+		assert!(matches!(broken.issues[..], [HealthIssue::BrokenProfile(_)]));
+	}
+
+	#[test]
+	fn test_scan_clusters_sorts_by_modified() {
+		let (old, new) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+		write(old.path(), "1.8.9", "1.8.9.json", r#"{"id":"1.8.9"}"#);
+		write(new.path(), "1.20.1", "1.20.1.json", r#"{"id":"1.20.1"}"#);
+		let json = old.path().join("versions/1.8.9/1.8.9.json");
+		fs::File::options()
+			.write(true)
+			.open(json)
+			.unwrap()
+			.set_modified(SystemTime::UNIX_EPOCH)
+			.unwrap();
+
+		let instances = InstanceScanner::scan_clusters(&[
+			old.path().to_path_buf(),
+			new.path().to_path_buf(),
+			old.path().join("missing"),
+		])
+		.unwrap();
+		let versions: Vec<_> = instances.iter().map(|i| i.version.as_str()).collect();
+		assert_eq!(versions, ["1.20.1", "1.8.9"]);
+	}
+}
@@ -1,33 +1,484 @@
-use anyhow::Result;
-use std::path::PathBuf;
+use crate::launcher::core::paths;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::RwLock;
 
-// TODO: 查找多个Java实例
-pub fn find_java(prefer: Option<PathBuf>) -> Result<PathBuf> {
-	if let Some(p) = prefer {
-		if p.exists() {
-			return Ok(p);
+const JAVA_BIN: &str = if cfg!(windows) { "java.exe" } else { "java" };
+
+/// 搜索子目录时的最大深度，Mojang 运行时的 bin 在第三层
+const SCAN_DEPTH: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JavaInstallation {
+	/// java 可执行文件
+	pub path: PathBuf,
+	pub home: PathBuf,
+	pub vendor: String,
+	pub version: String,
+	pub major: u32,
+	pub arch: String,
+}
+
+impl JavaInstallation {
+	/// 优先读取 `release` 文件，缺失时执行 java 读取系统属性
+	pub fn probe(java: &Path) -> Result<Self> {
+		let path = fs::canonicalize(java).with_context(|| format!("{}", java.display()))?;
+		let home = path
+			.parent()
+			.and_then(|bin| bin.parent())
+			.context("java binary has no home")?
+			.to_path_buf();
+
+		let props = read_release(&home).or_else(|| read_properties(&path));
+		let Some(props) = props else {
+			return Err(anyhow::anyhow!("cannot probe {}", path.display()));
+		};
+
+		let version = props.version.context("java version unknown")?;
+		let major = parse_major(&version).context("invalid java version")?;
+		Ok(Self {
+			path,
+			home,
+			vendor: props.vendor.unwrap_or_else(|| "Unknown".into()),
+			major,
+			version,
+			arch: props.arch.map(normalize_arch).unwrap_or_default(),
+		})
+	}
+
+	pub fn display_name(&self) -> String {
+		format!("Java {} ({}, {})", self.version, self.vendor, self.arch)
+	}
+}
+
+#[derive(Default)]
+struct JavaProps {
+	version: Option<String>,
+	vendor: Option<String>,
+	arch: Option<String>,
+}
+
+fn read_release(home: &Path) -> Option<JavaProps> {
+	let content = fs::read_to_string(home.join("release")).ok()?;
+	let mut props = JavaProps::default();
+	for line in content.lines() {
+		let Some((key, value)) = line.split_once('=') else {
+			continue;
+		};
+		let value = value.trim().trim_matches('"').to_string();
+		match key.trim() {
+			"JAVA_VERSION" => props.version = Some(value),
+			"IMPLEMENTOR" => props.vendor = Some(value),
+			"OS_ARCH" => props.arch = Some(value),
+			_ => {}
 		}
 	}
+	props.version.is_some().then_some(props)
+}
 
-	if let Ok(home) = std::env::var("JAVA_HOME") {
-		let candidate =
-			PathBuf::from(home)
-				.join("bin")
-				.join(if cfg!(windows) { "java.exe" } else { "java" });
-		if candidate.exists() {
-			return Ok(candidate);
+fn read_properties(java: &Path) -> Option<JavaProps> {
+	let output = Command::new(java)
+		.args(["-XshowSettings:properties", "-version"])
+		.output()
+		.ok()?;
+	// 属性输出在 stderr
+	let text = String::from_utf8_lossy(&output.stderr);
+	let mut props = JavaProps::default();
+	for line in text.lines() {
+		let Some((key, value)) = line.split_once(" = ") else {
+			continue;
+		};
+		let value = value.trim().to_string();
+		match key.trim() {
+			"java.version" => props.version = Some(value),
+			"java.vendor" => props.vendor = Some(value),
+			"os.arch" => props.arch = Some(value),
+			_ => {}
 		}
 	}
+	props.version.is_some().then_some(props)
+}
 
+/// `1.8.0_392` -> 8，`17.0.9` -> 17
+pub fn parse_major(version: &str) -> Option<u32> {
+	let mut parts = version.split(|c: char| !c.is_ascii_digit());
+	match parts.next()?.parse().ok()? {
+		1 => parts.next()?.parse().ok(),
+		major => Some(major),
+	}
+}
+
+fn normalize_arch(arch: String) -> String {
+	match arch.as_str() {
+		"amd64" | "x64" => "x86_64".into(),
+		"arm64" => "aarch64".into(),
+		"i386" | "i686" => "x86".into(),
+		_ => arch,
+	}
+}
+
+/// 启动器管理的 Java 运行时目录
+pub fn runtimes_dir() -> Result<PathBuf> {
+	Ok(paths::data_dir()?.join("runtimes"))
+}
+
+/// 可能存放 JDK 的目录，每个子目录视为一个 Java home
+fn search_roots() -> Vec<PathBuf> {
+	let mut roots: Vec<PathBuf> = [
+		"/usr/lib/jvm",
+		"/usr/lib64/jvm",
+		"/usr/java",
+		"/opt/java",
+		"/opt/jdk",
+		"/Library/Java/JavaVirtualMachines",
+	]
+	.iter()
+	.map(PathBuf::from)
+	.collect();
+
+	if let Some(home) = dirs::home_dir() {
+		roots.push(home.join(".sdkman/candidates/java"));
+		roots.push(home.join(".asdf/installs/java"));
+		roots.push(home.join(".jdks"));
+		roots.push(home.join(".local/share/mise/installs/java"));
+	}
+	if let Ok(sdkman) = std::env::var("SDKMAN_CANDIDATES_DIR") {
+		roots.push(PathBuf::from(sdkman).join("java"));
+	}
+	if let Ok(dir) = runtimes_dir() {
+		roots.push(dir);
+	}
+	roots
+}
+
+fn java_in_home(home: &Path) -> Option<PathBuf> {
+	[home.join("bin"), home.join("Contents/Home/bin")]
+		.into_iter()
+		.map(|bin| bin.join(JAVA_BIN))
+		.find(|p| p.is_file())
+}
+
+fn collect_homes(dir: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+	if let Some(java) = java_in_home(dir) {
+		out.push(java);
+		return;
+	}
+	if depth == 0 {
+		return;
+	}
+	let Ok(entries) = fs::read_dir(dir) else {
+		return;
+	};
+	for entry in entries.flatten() {
+		let path = entry.path();
+		if path.is_dir() {
+			collect_homes(&path, depth - 1, out);
+		}
+	}
+}
+
+/// 在目录（及其子目录）中找到 java 可执行文件
+pub fn java_in_dir(dir: &Path) -> Option<PathBuf> {
+	let mut found = Vec::new();
+	collect_homes(dir, SCAN_DEPTH, &mut found);
+	found.into_iter().next()
+}
+
+fn candidates() -> Vec<PathBuf> {
+	let mut found = Vec::new();
+
+	if let Ok(home) = std::env::var("JAVA_HOME") {
+		collect_homes(Path::new(&home), 0, &mut found);
+	}
 	if let Some(paths) = std::env::var_os("PATH") {
-		let name = if cfg!(windows) { "java.exe" } else { "java" };
 		for p in std::env::split_paths(&paths) {
-			let candidate = p.join(name);
-			if candidate.exists() {
-				return Ok(candidate);
+			let candidate = p.join(JAVA_BIN);
+			if candidate.is_file() {
+				found.push(candidate);
 			}
 		}
 	}
+	for root in search_roots() {
+		collect_homes(&root, SCAN_DEPTH, &mut found);
+	}
+	found
+}
 
-	Err(anyhow::anyhow!("Java runtime not found"))
+/// 扫描本机所有 Java，按主版本从高到低排序；同一安装的多个入口只保留一个
+pub fn scan_installations() -> Vec<JavaInstallation> {
+	let mut seen = HashSet::new();
+	let mut installs: Vec<JavaInstallation> = candidates()
+		.into_iter()
+		.filter_map(|java| match JavaInstallation::probe(&java) {
+			Ok(install) => Some(install),
+			Err(e) => {
+				tracing::debug!("Skip java {}: {}", java.display(), e);
+				None
+			}
+		})
+		.filter(|install| seen.insert(install.path.clone()))
+		.collect();
+	installs.sort_by(|a, b| b.major.cmp(&a.major).then_with(|| a.path.cmp(&b.path)));
+	installs
+}
+
+/// 已发现的 Java 列表，结果缓存在磁盘上，手动刷新时重新扫描
+pub struct JavaInventory {
+	installs: RwLock<Vec<JavaInstallation>>,
+	cache: Option<PathBuf>,
+}
+
+impl JavaInventory {
+	pub fn new() -> Result<Self> {
+		Ok(Self::open(paths::config_dir()?.join("java.yml")))
+	}
+
+	pub fn open(cache: impl Into<PathBuf>) -> Self {
+		let cache = cache.into();
+		let installs: Vec<JavaInstallation> = fs::read_to_string(&cache)
+			.ok()
+			.and_then(|s| serde_yaml::from_str(&s).ok())
+			.unwrap_or_default();
+		Self {
+			// 已卸载的条目直接丢弃
+			installs: RwLock::new(installs.into_iter().filter(|i| i.path.exists()).collect()),
+			cache: Some(cache),
+		}
+	}
+
+	pub fn list(&self) -> Vec<JavaInstallation> {
+		self.installs.read().unwrap().clone()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.installs.read().unwrap().is_empty()
+	}
+
+	/// 重新扫描并写入缓存，耗时操作，不要在 UI 线程调用
+	pub fn refresh(&self) -> Vec<JavaInstallation> {
+		let installs = scan_installations();
+		tracing::info!("Found {} Java installations", installs.len());
+		*self.installs.write().unwrap() = installs.clone();
+		self.save(&installs);
+		installs
+	}
+
+	/// 加入新安装的运行时，无需重新扫描
+	pub fn register(&self, install: JavaInstallation) {
+		let mut installs = self.installs.write().unwrap();
+		installs.retain(|i| i.path != install.path);
+		installs.push(install);
+		installs.sort_by(|a, b| b.major.cmp(&a.major).then_with(|| a.path.cmp(&b.path)));
+		let snapshot = installs.clone();
+		drop(installs);
+		self.save(&snapshot);
+	}
+
+	fn save(&self, installs: &[JavaInstallation]) {
+		let Some(cache) = &self.cache else {
+			return;
+		};
+		let result = serde_yaml::to_string(installs)
+			.map_err(anyhow::Error::from)
+			.and_then(|yaml| {
+				if let Some(dir) = cache.parent() {
+					fs::create_dir_all(dir)?;
+				}
+				Ok(fs::write(cache, yaml)?)
+			});
+		if let Err(e) = result {
+			tracing::warn!("Failed to save java cache: {}", e);
+		}
+	}
+
+	pub fn find(&self, java: &Path) -> Option<JavaInstallation> {
+		let java = fs::canonicalize(java).unwrap_or_else(|_| java.to_path_buf());
+		self.installs
+			.read()
+			.unwrap()
+			.iter()
+			.find(|i| i.path == java)
+			.cloned()
+	}
+}
+
+impl Default for JavaInventory {
+	fn default() -> Self {
+		Self::new().unwrap_or_else(|e| {
+			tracing::warn!("Failed to open java cache: {}", e);
+			Self {
+				installs: RwLock::new(Vec::new()),
+				cache: None,
+			}
+		})
+	}
+}
+
+/// 版本对 Java 的要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaRequirement {
+	pub major: u32,
+	/// 版本 JSON 明确写出了 javaVersion；缺失时 `major` 只是按 Java 8 估计
+	pub explicit: bool,
+}
+
+impl JavaRequirement {
+	/// 明确要求 Java 8 的版本（尤其是 Mod 加载器）只能用 8，
+	/// 其余情况允许使用更高的 Java
+	pub fn is_compatible(&self, major: u32) -> bool {
+		if self.explicit && self.major <= 8 {
+			major == self.major
+		} else {
+			major >= self.major
+		}
+	}
+}
+
+/// 在清单中挑选满足要求的 Java：版本完全一致优先，其次是最接近的更高版本
+pub fn select_java(
+	required: JavaRequirement,
+	installs: &[JavaInstallation],
+) -> Option<&JavaInstallation> {
+	let arch = normalize_arch(std::env::consts::ARCH.to_string());
+	let usable = installs
+		.iter()
+		.filter(|i| i.arch.is_empty() || i.arch == arch)
+		.filter(|i| required.is_compatible(i.major));
+	usable.min_by_key(|i| (i.major != required.major, i.major))
+}
+
+/// 选择启动用的 Java
+///
+/// `explicit` 为实例单独指定的路径，原样使用；`preferred` 为全局默认，
+/// 仅在版本满足要求时使用，否则按 `required` 从清单中自动挑选
+pub fn find_java(
+	explicit: Option<PathBuf>,
+	preferred: Option<PathBuf>,
+	required: JavaRequirement,
+	installs: &[JavaInstallation],
+) -> Result<PathBuf> {
+	let major = required.major;
+	if let Some(p) = explicit {
+		if !p.exists() {
+			return Err(anyhow::anyhow!(
+				"Java {} set for this instance does not exist",
+				p.display()
+			));
+		}
+		let installed = installed_major(&p, installs);
+		if installed.is_some_and(|m| !required.is_compatible(m)) {
+			tracing::warn!(
+				"Instance java {} is Java {}, but this version requires Java {}",
+				p.display(),
+				installed.unwrap_or_default(),
+				major
+			);
+		}
+		return Ok(p);
+	}
+
+	if let Some(p) = preferred.filter(|p| p.exists()) {
+		match installed_major(&p, installs) {
+			Some(installed) if required.is_compatible(installed) => return Ok(p),
+			// 没有 javaVersion 的自定义或加载器 JSON 无从判断，沿用用户选择的默认 Java
+			_ if !required.explicit => return Ok(p),
+			installed => tracing::info!(
+				"Default java {} ({:?}) does not satisfy Java {}, selecting automatically",
+				p.display(),
+				installed,
+				major
+			),
+		}
+	}
+
+	select_java(required, installs)
+		.map(|i| i.path.clone())
+		.ok_or_else(|| {
+			anyhow::anyhow!(
+				"This version requires Java {major}, but no matching Java was found. \
+				 Install Java {major} or choose a Java in the instance settings"
+			)
+		})
+}
+
+fn installed_major(java: &Path, installs: &[JavaInstallation]) -> Option<u32> {
+	let canonical = fs::canonicalize(java).ok()?;
+	installs
+		.iter()
+		.find(|i| i.path == canonical)
+		.map(|i| i.major)
+		.or_else(|| JavaInstallation::probe(java).ok().map(|i| i.major))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_probe_release_file() {
+		let dir = tempfile::tempdir().unwrap();
+		let home = dir.path().join("jdk-17");
+		fs::create_dir_all(home.join("bin")).unwrap();
+		fs::write(home.join("bin").join(JAVA_BIN), b"").unwrap();
+		fs::write(
+			home.join("release"),
+			"IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"17.0.9\"\nOS_ARCH=\"amd64\"\n",
+		)
+		.unwrap();
+
+		let mut found = Vec::new();
+		collect_homes(dir.path(), SCAN_DEPTH, &mut found);
+		assert_eq!(found.len(), 1);
+
+		let install = JavaInstallation::probe(&found[0]).unwrap();
+		assert_eq!(install.major, 17);
+		assert_eq!(install.vendor, "Eclipse Adoptium");
+		assert_eq!(install.arch, "x86_64");
+
+		assert_eq!(parse_major("1.8.0_392"), Some(8));
+		assert_eq!(parse_major("21"), Some(21));
+	}
+
+	#[test]
+	fn test_select_java() {
+		let arch = normalize_arch(std::env::consts::ARCH.to_string());
+		let installs: Vec<JavaInstallation> = [21, 17, 8]
+			.into_iter()
+			.map(|major| JavaInstallation {
+				path: PathBuf::from(format!("/jdk-{major}/bin/java")),
+				home: PathBuf::from(format!("/jdk-{major}")),
+				vendor: "Test".into(),
+				version: major.to_string(),
+				major,
+				arch: arch.clone(),
+			})
+			.collect();
+
+		let required = |major, explicit| JavaRequirement { major, explicit };
+		assert_eq!(
+			select_java(required(17, true), &installs).unwrap().major,
+			17
+		);
+		assert_eq!(
+			select_java(required(16, true), &installs).unwrap().major,
+			17
+		);
+		assert_eq!(select_java(required(8, true), &installs).unwrap().major, 8);
+		assert!(select_java(required(25, true), &installs).is_none());
+
+		// 没有 javaVersion 时 Java 8 以上均可，默认 Java 不会被拒绝
+		let installs = &installs[..2];
+		assert_eq!(select_java(required(8, true), installs), None);
+		assert_eq!(select_java(required(8, false), installs).unwrap().major, 17);
+		let dir = tempfile::tempdir().unwrap();
+		let preferred = dir.path().join("java");
+		fs::write(&preferred, "").unwrap();
+		let found = find_java(None, Some(preferred.clone()), required(8, false), installs);
+		assert_eq!(found.unwrap(), preferred);
+		assert!(find_java(None, Some(preferred), required(8, true), installs).is_err());
+	}
 }
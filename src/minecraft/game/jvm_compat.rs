@@ -0,0 +1,222 @@
+use crate::minecraft::game::java::JavaInstallation;
+
+/// 单条 JVM 参数的版本要求，`flag` 以 `=`、`:` 结尾时按前缀匹配
+struct ArgRule {
+	flag: &'static str,
+	/// 最低支持的主版本
+	since: Option<u32>,
+	/// 从该主版本起已移除
+	removed: Option<u32>,
+	/// 仅 OpenJDK 系构建提供（Oracle JDK 没有）
+	openjdk_only: bool,
+	/// 需要 `-XX:+UnlockExperimentalVMOptions` 的版本范围上限（不含）
+	experimental_until: Option<u32>,
+}
+
+const fn rule(flag: &'static str) -> ArgRule {
+	ArgRule {
+		flag,
+		since: None,
+		removed: None,
+		openjdk_only: false,
+		experimental_until: None,
+	}
+}
+
+const RULES: &[ArgRule] = &[
+	ArgRule {
+		since: Some(11),
+		experimental_until: Some(15),
+		..rule("-XX:+UseZGC")
+	},
+	ArgRule {
+		since: Some(21),
+		removed: Some(24),
+		..rule("-XX:+ZGenerational")
+	},
+	ArgRule {
+		since: Some(12),
+		openjdk_only: true,
+		..rule("-XX:+UseShenandoahGC")
+	},
+	ArgRule {
+		removed: Some(14),
+		..rule("-XX:+UseConcMarkSweepGC")
+	},
+	ArgRule {
+		removed: Some(12),
+		..rule("-XX:+AggressiveOpts")
+	},
+	ArgRule {
+		removed: Some(16),
+		..rule("-XX:+UseLargePagesInMetaspace")
+	},
+	ArgRule {
+		removed: Some(9),
+		..rule("-XX:MaxPermSize=")
+	},
+	ArgRule {
+		removed: Some(9),
+		..rule("-XX:PermSize=")
+	},
+	ArgRule {
+		since: Some(9),
+		..rule("--add-opens")
+	},
+	ArgRule {
+		since: Some(9),
+		..rule("--add-exports")
+	},
+	ArgRule {
+		since: Some(9),
+		..rule("--add-modules")
+	},
+	ArgRule {
+		since: Some(9),
+		removed: Some(17),
+		..rule("--illegal-access=")
+	},
+	ArgRule {
+		since: Some(9),
+		..rule("-XX:+EnableJVMCI")
+	},
+	ArgRule {
+		since: Some(10),
+		..rule("-XX:+UseJVMCICompiler")
+	},
+	// G1 的这些调优参数一直是实验性的
+	ArgRule {
+		experimental_until: Some(u32::MAX),
+		..rule("-XX:G1NewSizePercent=")
+	},
+	ArgRule {
+		experimental_until: Some(u32::MAX),
+		..rule("-XX:G1MaxNewSizePercent=")
+	},
+];
+
+const UNLOCK_EXPERIMENTAL: &str = "-XX:+UnlockExperimentalVMOptions";
+
+impl ArgRule {
+	fn matches(&self, arg: &str) -> bool {
+		if self.flag.ends_with('=') || self.flag.ends_with(':') {
+			arg.starts_with(self.flag)
+		} else {
+			arg == self.flag || arg.starts_with(&format!("{}=", self.flag))
+		}
+	}
+
+	/// 不支持时返回原因
+	fn unsupported(&self, java: &JavaInstallation) -> Option<String> {
+		if let Some(since) = self.since
+			&& java.major < since
+		{
+			return Some(format!("requires Java {since}+"));
+		}
+		if let Some(removed) = self.removed
+			&& java.major >= removed
+		{
+			return Some(format!("removed in Java {removed}"));
+		}
+		if self.openjdk_only && java.vendor.to_lowercase().contains("oracle") {
+			return Some(format!("not available in {}", java.vendor));
+		}
+		None
+	}
+}
+
+/// 过滤或改写当前 JVM 不支持的用户参数，返回处理后的参数与每一处改动的说明
+pub fn filter_jvm_args(args: Vec<String>, java: &JavaInstallation) -> (Vec<String>, Vec<String>) {
+	let mut out = Vec::with_capacity(args.len());
+	let mut warnings = Vec::new();
+	let mut needs_unlock = false;
+
+	let mut iter = args.into_iter().peekable();
+	while let Some(arg) = iter.next() {
+		let Some(rule) = RULES.iter().find(|r| r.matches(&arg)) else {
+			out.push(arg);
+			continue;
+		};
+
+		if let Some(reason) = rule.unsupported(java) {
+			// `--add-opens a/b=ALL-UNNAMED` 这类参数的值可能是下一项
+			let value = (!arg.contains('=') && arg.starts_with("--"))
+				.then(|| iter.next_if(|next| !next.starts_with('-')))
+				.flatten();
+			let shown = match value {
+				Some(v) => format!("{arg} {v}"),
+				None => arg,
+			};
+			warnings.push(format!(
+				"Removed JVM argument {shown}: {reason} (using Java {})",
+				java.major
+			));
+			continue;
+		}
+
+		if rule
+			.experimental_until
+			.is_some_and(|until| java.major < until)
+		{
+			needs_unlock = true;
+		}
+		out.push(arg);
+	}
+
+	if needs_unlock && !out.iter().any(|a| a == UNLOCK_EXPERIMENTAL) {
+		warnings.push(format!(
+			"Added {UNLOCK_EXPERIMENTAL} required by experimental options on Java {}",
+			java.major
+		));
+		out.insert(0, UNLOCK_EXPERIMENTAL.to_string());
+	}
+
+	(out, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::path::PathBuf;
+
+	fn java(major: u32) -> JavaInstallation {
+		JavaInstallation {
+			path: PathBuf::from("/jdk/bin/java"),
+			home: PathBuf::from("/jdk"),
+			vendor: "Eclipse Adoptium".into(),
+			version: major.to_string(),
+			major,
+			arch: "x86_64".into(),
+		}
+	}
+
+	fn args(s: &str) -> Vec<String> {
+		s.split_whitespace().map(String::from).collect()
+	}
+
+	#[test]
+	fn test_filter_for_java8() {
+		let (out, warnings) = filter_jvm_args(
+			args("-XX:+UseZGC --add-opens java.base/java.lang=ALL-UNNAMED -XX:+UseG1GC"),
+			&java(8),
+		);
+		assert_eq!(out, args("-XX:+UseG1GC"));
+		assert_eq!(warnings.len(), 2);
+	}
+
+	#[test]
+	fn test_unlock_experimental() {
+		let (out, warnings) =
+			filter_jvm_args(args("-XX:+UseZGC -XX:+UseConcMarkSweepGC"), &java(11));
+		assert_eq!(
+			out,
+			args("-XX:+UnlockExperimentalVMOptions -XX:+UseZGC -XX:+UseConcMarkSweepGC")
+		);
+		assert_eq!(warnings.len(), 1);
+
+		let (out, warnings) =
+			filter_jvm_args(args("-XX:+UseZGC -XX:+UseConcMarkSweepGC"), &java(21));
+		assert_eq!(out, args("-XX:+UseZGC"));
+		assert_eq!(warnings.len(), 1);
+	}
+}
@@ -0,0 +1,281 @@
+use crate::infrastructure::network::download::{
+	Checksum, DownloadClient, DownloadError, DownloadRequest,
+};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
+
+/// Mojang 库仓库，没有给出 `url` 的库默认从这里下载
+pub const MOJANG_LIBRARIES: &str = "https://libraries.minecraft.net/";
+
+/// Maven 坐标 `group:artifact:version[:classifier][@extension]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MavenCoord {
+	pub group: String,
+	pub artifact: String,
+	pub version: String,
+	pub classifier: Option<String>,
+	pub extension: String,
+}
+
+impl MavenCoord {
+	pub fn parse(coord: &str) -> Result<Self> {
+		let (coord, extension) = match coord.split_once('@') {
+			Some((c, ext)) => (c, ext.to_string()),
+			None => (coord, "jar".to_string()),
+		};
+		let parts: Vec<&str> = coord.split(':').collect();
+		if parts.len() < 3 || parts[..3].iter().any(|p| p.is_empty()) {
+			return Err(anyhow::anyhow!("Invalid maven coord: {coord}"));
+		}
+		Ok(Self {
+			group: parts[0].to_string(),
+			artifact: parts[1].to_string(),
+			version: parts[2].to_string(),
+			classifier: parts.get(3).map(|c| c.to_string()),
+			extension,
+		})
+	}
+
+	pub fn with_classifier(mut self, classifier: Option<&str>) -> Self {
+		if let Some(c) = classifier {
+			self.classifier = Some(c.to_string());
+		}
+		self
+	}
+
+	pub fn file_name(&self) -> String {
+		match &self.classifier {
+			Some(c) => format!("{}-{}-{c}.{}", self.artifact, self.version, self.extension),
+			None => format!("{}-{}.{}", self.artifact, self.version, self.extension),
+		}
+	}
+
+	/// 仓库内的相对路径，用 `/` 分隔
+	pub fn path(&self) -> String {
+		format!(
+			"{}/{}/{}/{}",
+			self.group.replace('.', "/"),
+			self.artifact,
+			self.version,
+			self.file_name()
+		)
+	}
+
+	pub fn local_path(&self, libraries_dir: &Path) -> PathBuf {
+		libraries_dir.join(self.path().replace('/', std::path::MAIN_SEPARATOR_STR))
+	}
+
+	pub fn url(&self, repository: &str) -> String {
+		format!("{}/{}", repository.trim_end_matches('/'), self.path())
+	}
+}
+
+/// 依次尝试的仓库：库自带的 `url` 优先，其后是配置的备用仓库，去重
+pub fn repositories(library_url: Option<&str>, fallbacks: &[String]) -> Vec<String> {
+	let mut repos: Vec<String> = Vec::new();
+	let primary = library_url
+		.filter(|u| !u.is_empty())
+		.unwrap_or(MOJANG_LIBRARIES);
+	for repo in std::iter::once(primary).chain(fallbacks.iter().map(String::as_str)) {
+		let repo = repo.trim_end_matches('/');
+		if !repos.iter().any(|r| r == repo) {
+			repos.push(repo.to_string());
+		}
+	}
+	repos
+}
+
+/// 读取 `.sha1` 旁路文件，仓库没有提供时返回 None
+pub async fn fetch_sha1(url: &str) -> Option<String> {
+	let resp = reqwest::get(format!("{url}.sha1")).await.ok()?;
+	if !resp.status().is_success() {
+		return None;
+	}
+	let text = resp.text().await.ok()?;
+	// 部分仓库的格式为 `<hash>  <file>`
+	let hash = text.split_whitespace().next()?.to_lowercase();
+	(hash.len() == 40 && hash.chars().all(|c| c.is_ascii_hexdigit())).then_some(hash)
+}
+
+/// 按顺序从各仓库下载构件，已知 SHA-1 时直接校验，否则使用仓库的 `.sha1` 文件
+pub async fn download_artifact(
+	client: &DownloadClient,
+	coord: &MavenCoord,
+	repos: &[String],
+	sha1: Option<&str>,
+	dest: &Path,
+	cancel: Option<watch::Receiver<bool>>,
+) -> Result<()> {
+	let mut errors = Vec::new();
+	for repo in repos {
+		let url = coord.url(repo);
+		let sha1 = match sha1 {
+			Some(s) => Some(s.to_string()),
+			None => fetch_sha1(&url).await,
+		};
+		if sha1.is_none() {
+			tracing::warn!(
+				"{} has no SHA-1 in {}, downloading unverified",
+				coord.path(),
+				repo
+			);
+		}
+		let mut request = DownloadRequest::new(url.clone(), dest);
+		if let Some(sha1) = sha1 {
+			request = request.with_checksum(Checksum::Sha1(sha1));
+		}
+		match client.download(request, |_| {}, cancel.clone()).await {
+			Ok(()) => return Ok(()),
+			Err(DownloadError::Cancelled) => return Err(DownloadError::Cancelled.into()),
+			Err(e) => {
+				tracing::debug!("{} unavailable from {}: {}", coord.path(), repo, e);
+				// 不同仓库的半截文件不能续传
+				let _ = tokio::fs::remove_file(dest.with_extension("hako.part")).await;
+				errors.push(format!("{url}: {e}"));
+			}
+		}
+	}
+	Err(anyhow::anyhow!(
+		"Download {} failed: {}",
+		coord.path(),
+		errors.join("; ")
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::{request_path, serve};
+	use std::sync::{Arc, Mutex};
+
+	#[test]
+	fn test_parse_coord() {
+		let coord =
+			MavenCoord::parse("net.minecraftforge:forge:1.20.1-47.2.0:installer@zip").unwrap();
+		assert_eq!(
+			coord.path(),
+			"net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-installer.zip"
+		);
+		assert_eq!(
+			coord.url("https://maven.minecraftforge.net/"),
+			"https://maven.minecraftforge.net/net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-installer.zip"
+		);
+
+		let coord = MavenCoord::parse("net.fabricmc:intermediary:1.20.1").unwrap();
+		assert_eq!(coord.file_name(), "intermediary-1.20.1.jar");
+		assert!(MavenCoord::parse("net.fabricmc:intermediary").is_err());
+	}
+
+	const JAR: &str = "/org/example/lib/1.0/lib-1.0.jar";
+
+	fn sha1_hex(data: &[u8]) -> String {
+		use sha1::{Digest, Sha1};
+		hex::encode(Sha1::digest(data))
+	}
+
+	// 每个仓库返回 (jar 内容, .sha1 内容)，None 表示 404；记录请求顺序
+	async fn serve_repos(
+		repos: Vec<(&'static str, Option<&'static str>, Option<String>)>,
+	) -> (String, Arc<Mutex<Vec<String>>>) {
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let log = Arc::clone(&requests);
+		let url = serve(move |req| {
+			let path = request_path(req).to_string();
+			log.lock().unwrap().push(path.clone());
+			let found = repos.iter().find_map(|(prefix, jar, sha1)| {
+				let rest = path.strip_prefix(prefix)?;
+				match rest.strip_suffix(".sha1") {
+					Some(_) => sha1.clone(),
+					None => jar.map(str::to_string),
+				}
+			});
+			match found {
+				Some(body) => ("200 OK", body),
+				None => ("404 Not Found", String::new()),
+			}
+		})
+		.await;
+		(url, requests)
+	}
+
+	#[tokio::test]
+	async fn test_download_artifact_falls_back_on_bad_sha1() {
+		let (url, requests) = serve_repos(vec![
+			("/a", Some("tampered"), Some(sha1_hex(b"jar"))),
+			("/b", Some("jar"), Some(sha1_hex(b"jar"))),
+		])
+		.await;
+		let dir = tempfile::tempdir().unwrap();
+		let dest = dir.path().join("lib.jar");
+		let coord = MavenCoord::parse("org.example:lib:1.0").unwrap();
+		let repos = [format!("{url}/a"), format!("{url}/b")];
+
+		let client = DownloadClient::new().unwrap();
+		download_artifact(&client, &coord, &repos, None, &dest, None)
+			.await
+			.unwrap();
+		assert_eq!(std::fs::read_to_string(&dest).unwrap(), "jar");
+		assert_eq!(
+			*requests.lock().unwrap(),
+			[
+				format!("/a{JAR}.sha1"),
+				format!("/a{JAR}"),
+				format!("/b{JAR}.sha1"),
+				format!("/b{JAR}"),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_download_artifact_rejects_sha1_mismatch() {
+		let (url, _) = serve_repos(vec![("/a", Some("tampered"), Some(sha1_hex(b"jar")))]).await;
+		let dir = tempfile::tempdir().unwrap();
+		let dest = dir.path().join("lib.jar");
+		let coord = MavenCoord::parse("org.example:lib:1.0").unwrap();
+
+		let client = DownloadClient::new().unwrap();
+		let result =
+			download_artifact(&client, &coord, &[format!("{url}/a")], None, &dest, None).await;
+		assert!(result.is_err());
+		assert!(!dest.exists());
+	}
+
+	#[derive(Clone, Default)]
+	struct Captured(Arc<Mutex<Vec<u8>>>);
+
+	impl std::io::Write for Captured {
+		fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+			self.0.lock().unwrap().extend_from_slice(buf);
+			Ok(buf.len())
+		}
+
+		fn flush(&mut self) -> std::io::Result<()> {
+			Ok(())
+		}
+	}
+
+	#[tokio::test]
+	async fn test_download_artifact_without_sha1_warns() {
+		let (url, _) = serve_repos(vec![("/a", Some("jar"), None)]).await;
+		let dir = tempfile::tempdir().unwrap();
+		let dest = dir.path().join("lib.jar");
+		let coord = MavenCoord::parse("org.example:lib:1.0").unwrap();
+
+		let logs = Captured::default();
+		let writer = logs.clone();
+		let subscriber = tracing_subscriber::fmt()
+			.with_writer(move || writer.clone())
+			.with_max_level(tracing::Level::WARN)
+			.finish();
+		let _guard = tracing::subscriber::set_default(subscriber);
+
+		let client = DownloadClient::new().unwrap();
+		download_artifact(&client, &coord, &[format!("{url}/a")], None, &dest, None)
+			.await
+			.unwrap();
+		assert_eq!(std::fs::read_to_string(&dest).unwrap(), "jar");
+		let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+		assert!(logs.contains("downloading unverified"));
+	}
+}
@@ -1,5 +1,10 @@
+pub mod adoptium;
 pub mod args;
+pub mod authlib;
 pub mod classpath;
 pub mod instance;
 pub mod java;
+pub mod jvm_compat;
+pub mod maven;
 pub mod natives;
+pub mod runtime;
@@ -0,0 +1,251 @@
+use crate::minecraft::game::java::runtimes_dir;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Mojang 官方 Java 运行时总清单
+pub const RUNTIME_MANIFEST: &str = "https://launchermeta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+/// 安装完成后写入运行时目录，内容为版本名
+const MARKER: &str = ".hako-runtime";
+
+/// platform -> component -> 可用版本
+pub type RuntimeIndex = HashMap<String, HashMap<String, Vec<RuntimeEntry>>>;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuntimeEntry {
+	pub manifest: RemoteFile,
+	pub version: RuntimeVersion,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuntimeVersion {
+	pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteFile {
+	pub sha1: String,
+	pub size: u64,
+	pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RuntimeManifest {
+	pub files: HashMap<String, RuntimeFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RuntimeFile {
+	Directory,
+	File {
+		#[serde(default)]
+		executable: bool,
+		downloads: FileDownloads,
+	},
+	Link {
+		target: String,
+	},
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FileDownloads {
+	pub raw: RemoteFile,
+	#[serde(default)]
+	pub lzma: Option<RemoteFile>,
+}
+
+/// 运行时清单中的平台名，没有官方运行时的平台返回 None
+pub fn platform_key() -> Option<&'static str> {
+	platform_key_for(std::env::consts::OS, std::env::consts::ARCH)
+}
+
+fn platform_key_for(os: &str, arch: &str) -> Option<&'static str> {
+	match (os, arch) {
+		("linux", "x86_64") => Some("linux"),
+		("linux", "x86") => Some("linux-i386"),
+		("macos", "x86_64") => Some("mac-os"),
+		("macos", "aarch64") => Some("mac-os-arm64"),
+		("windows", "x86_64") => Some("windows-x64"),
+		("windows", "x86") => Some("windows-x86"),
+		("windows", "aarch64") => Some("windows-arm64"),
+		_ => None,
+	}
+}
+
+pub fn runtime_dir(component: &str) -> Result<PathBuf> {
+	Ok(runtimes_dir()?.join(component))
+}
+
+pub fn installed_version(dir: &Path) -> Option<String> {
+	fs::read_to_string(dir.join(MARKER))
+		.ok()
+		.map(|s| s.trim().to_string())
+}
+
+pub fn mark_installed(dir: &Path, version: &str) -> Result<()> {
+	fs::write(dir.join(MARKER), version).context("write runtime marker")
+}
+
+/// 删除目录中清单已不再包含的文件，`keep` 为清单内全部条目的完整路径
+pub fn prune_runtime(dir: &Path, keep: &HashSet<PathBuf>) -> Result<()> {
+	if !dir.exists() {
+		return Ok(());
+	}
+	for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+		let path = entry?.path();
+		if path.ends_with(MARKER) {
+			continue;
+		}
+		let is_dir = fs::symlink_metadata(&path)?.is_dir();
+		if is_dir && keep.iter().any(|k| k.starts_with(&path)) {
+			prune_runtime(&path, keep)?;
+		} else if is_dir {
+			fs::remove_dir_all(&path)?;
+		} else if !keep.contains(&path) {
+			fs::remove_file(&path)?;
+		}
+	}
+	Ok(())
+}
+
+/// 解压 Mojang 的 .lzma 文件
+pub fn decompress_lzma(src: &Path, dest: &Path) -> Result<()> {
+	let input = std::io::BufReader::new(fs::File::open(src)?);
+	let mut reader =
+		lzma_rust2::LzmaReader::new_mem_limit(input, u32::MAX, None).context("read lzma header")?;
+	let mut output = fs::File::create(dest)?;
+	std::io::copy(&mut reader, &mut output).context("decompress lzma")?;
+	Ok(())
+}
+
+#[cfg(unix)]
+pub fn set_executable(path: &Path) -> Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+	let mut perms = fs::metadata(path)?.permissions();
+	perms.set_mode(perms.mode() | 0o755);
+	fs::set_permissions(path, perms)?;
+	Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn set_executable(_path: &Path) -> Result<()> {
+	Ok(())
+}
+
+/// 重建清单中的符号链接，已存在的同名文件会被替换
+#[cfg(unix)]
+pub fn create_link(path: &Path, target: &str) -> Result<()> {
+	if fs::symlink_metadata(path).is_ok() {
+		fs::remove_file(path)?;
+	}
+	std::os::unix::fs::symlink(target, path)
+		.with_context(|| format!("link {} -> {}", path.display(), target))
+}
+
+// Windows 运行时的清单中没有链接
+#[cfg(not(unix))]
+pub fn create_link(_path: &Path, _target: &str) -> Result<()> {
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_manifest() {
+		let manifest: RuntimeManifest = serde_json::from_str(
+			r#"{"files":{
+				"bin":{"type":"directory"},
+				"bin/java":{"type":"file","executable":true,"downloads":{
+					"raw":{"sha1":"aa","size":10,"url":"http://raw"},
+					"lzma":{"sha1":"bb","size":5,"url":"http://lzma"}}},
+				"lib/legal":{"type":"link","target":"../legal"}}}"#,
+		)
+		.unwrap();
+
+		assert!(matches!(manifest.files["bin"], RuntimeFile::Directory));
+		let RuntimeFile::File {
+			executable,
+			downloads,
+		} = &manifest.files["bin/java"]
+		else {
+			panic!("bin/java is not a file");
+		};
+		assert!(*executable);
+		assert_eq!(downloads.raw.size, 10);
+		assert_eq!(downloads.lzma.as_ref().unwrap().url, "http://lzma");
+		assert!(matches!(
+			&manifest.files["lib/legal"],
+			RuntimeFile::Link { target } if target == "../legal"
+		));
+	}
+
+	#[test]
+	fn test_platform_key() {
+		assert_eq!(platform_key_for("linux", "x86_64"), Some("linux"));
+		assert_eq!(platform_key_for("macos", "aarch64"), Some("mac-os-arm64"));
+		assert_eq!(platform_key_for("windows", "x86"), Some("windows-x86"));
+		assert_eq!(platform_key_for("linux", "aarch64"), None);
+	}
+
+	#[test]
+	fn test_decompress_lzma() {
+		// `lzma.compress(b"hello runtime\n", format=lzma.FORMAT_ALONE)`
+		const PACKED: [u8; 37] = [
+			0x5d, 0x00, 0x00, 0x80, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00,
+			0x34, 0x19, 0x49, 0xee, 0x8d, 0xe9, 0x16, 0x2c, 0xd5, 0x81, 0xf2, 0xac, 0xe0, 0x2d,
+			0xfb, 0x37, 0x58, 0x82, 0xff, 0xfe, 0x8c, 0xa8, 0x00,
+		];
+		let dir = tempfile::tempdir().unwrap();
+		let (src, dest) = (dir.path().join("java.lzma"), dir.path().join("java"));
+		fs::write(&src, PACKED).unwrap();
+
+		decompress_lzma(&src, &dest).unwrap();
+		assert_eq!(fs::read(&dest).unwrap(), b"hello runtime\n");
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_executable_and_link() {
+		use std::os::unix::fs::PermissionsExt;
+		let dir = tempfile::tempdir().unwrap();
+		let java = dir.path().join("java");
+		fs::write(&java, b"").unwrap();
+		set_executable(&java).unwrap();
+		assert_eq!(
+			fs::metadata(&java).unwrap().permissions().mode() & 0o111,
+			0o111
+		);
+
+		let link = dir.path().join("legal");
+		fs::write(&link, b"stale").unwrap();
+		create_link(&link, "java").unwrap();
+		assert_eq!(fs::read_link(&link).unwrap(), Path::new("java"));
+	}
+
+	#[test]
+	fn test_prune_runtime() {
+		let dir = tempfile::tempdir().unwrap();
+		let root = dir.path();
+		for file in ["bin/java", "bin/old", "stale/lib.so", MARKER] {
+			let path = root.join(file);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, b"").unwrap();
+		}
+
+		prune_runtime(
+			root,
+			&HashSet::from([root.join("bin"), root.join("bin/java")]),
+		)
+		.unwrap();
+		assert!(root.join("bin/java").exists());
+		assert!(root.join(MARKER).exists());
+		assert!(!root.join("bin/old").exists());
+		assert!(!root.join("stale").exists());
+	}
+}
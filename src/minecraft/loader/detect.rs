@@ -0,0 +1,183 @@
+use crate::minecraft::game::instance::InstanceMeta;
+use crate::minecraft::game::maven::MavenCoord;
+use crate::minecraft::loader::{LoaderInfo, LoaderKind};
+use crate::minecraft::profile::{
+	ArgumentValue, VersionProfile, load_version_profile, root_version,
+};
+use anyhow::Result;
+use std::path::Path;
+
+/// 从版本 JSON 推断出的实例信息
+#[derive(Debug, Clone)]
+pub struct DetectedInstance {
+	pub game_version: String,
+	pub loader: Option<LoaderInfo>,
+	/// 合并 inheritsFrom 后的完整版本 JSON
+	pub profile: VersionProfile,
+}
+
+/// 按 inheritsFrom 链与库名识别实例的原版版本与加载器，不依赖 Hako 的记录
+pub fn detect_instance(cluster_path: &Path, version: &str) -> Result<DetectedInstance> {
+	let profile = load_version_profile(cluster_path, version)?;
+	let root = root_version(cluster_path, version)?;
+	// 其他启动器合并成单个 JSON 时会写入 clientVersion
+	let game_version = if root != version {
+		root
+	} else if let Some(client) = profile.extra.get("clientVersion").and_then(|v| v.as_str()) {
+		client.to_string()
+	} else {
+		InstanceMeta::load(cluster_path, version)
+			.game_version
+			.unwrap_or(root)
+	};
+	let loader = detect_loader(&profile, &game_version);
+	Ok(DetectedInstance {
+		game_version,
+		loader,
+		profile,
+	})
+}
+
+/// 同时带有 Forge 系与 OptiFine 时以前者为准
+pub fn detect_loader(profile: &VersionProfile, game_version: &str) -> Option<LoaderInfo> {
+	let game_args: Vec<&str> = profile
+		.arguments
+		.iter()
+		.flat_map(|a| &a.game)
+		.filter_map(|v| match v {
+			ArgumentValue::Plain(s) => Some(s.as_str()),
+			ArgumentValue::Obj(_) => None,
+		})
+		.collect();
+	let arg = |name: &str| {
+		game_args
+			.windows(2)
+			.find(|w| w[0] == name)
+			.map(|w| w[1].to_string())
+	};
+	let coords: Vec<MavenCoord> = profile
+		.libraries
+		.iter()
+		.filter_map(|lib| MavenCoord::parse(&lib.name).ok())
+		.collect();
+	let find = |group: &str, artifacts: &[&str]| {
+		coords
+			.iter()
+			.find(|c| c.group == group && artifacts.contains(&c.artifact.as_str()))
+	};
+	// Forge 系的库版本带有 `<game>-` 前缀
+	let strip = |v: &str| {
+		v.strip_prefix(&format!("{game_version}-"))
+			.unwrap_or(v)
+			.to_string()
+	};
+	let info = |kind, version| Some(LoaderInfo { kind, version });
+
+	if let Some(v) = arg("--fml.neoForgeVersion") {
+		return info(LoaderKind::NeoForge, v);
+	}
+	// 1.20.1 的 NeoForge 仍沿用 Forge 的参数与库名，只是 group 不同
+	let neoforged = coords.iter().any(|c| c.group == "net.neoforged");
+	if let Some(v) = arg("--fml.forgeVersion") {
+		let kind = if neoforged {
+			LoaderKind::NeoForge
+		} else {
+			LoaderKind::Forge
+		};
+		return info(kind, v);
+	}
+	if let Some(c) = find("net.neoforged", &["neoforge", "forge"]) {
+		return info(LoaderKind::NeoForge, strip(&c.version));
+	}
+	if let Some(c) = find(
+		"net.minecraftforge",
+		&["forge", "minecraftforge", "fmlloader"],
+	) {
+		return info(LoaderKind::Forge, strip(&c.version));
+	}
+	if let Some(c) = find("net.fabricmc", &["fabric-loader"]) {
+		return info(LoaderKind::Fabric, c.version.clone());
+	}
+	if let Some(c) = find("org.quiltmc", &["quilt-loader"]) {
+		return info(LoaderKind::Quilt, c.version.clone());
+	}
+	if let Some(c) = find("optifine", &["OptiFine"]) {
+		let version = c
+			.version
+			.strip_prefix(&format!("{game_version}_"))
+			.unwrap_or(&c.version);
+		return info(LoaderKind::OptiFine, version.to_string());
+	}
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::minecraft::profile::{Arguments, Library};
+
+	fn profile(libraries: &[&str], game: &[&str]) -> VersionProfile {
+		VersionProfile {
+			libraries: libraries
+				.iter()
+				.map(|name| Library {
+					name: name.to_string(),
+					..Default::default()
+				})
+				.collect(),
+			arguments: Some(Arguments {
+				game: game
+					.iter()
+					.map(|a| ArgumentValue::Plain(a.to_string()))
+					.collect(),
+				jvm: Vec::new(),
+			}),
+			..Default::default()
+		}
+	}
+
+	#[test]
+	fn test_detect_loader() {
+		let fabric = profile(
+			&["org.ow2.asm:asm:9.6", "net.fabricmc:fabric-loader:0.15.0"],
+			&[],
+		);
+		assert_eq!(
+			detect_loader(&fabric, "1.20.1"),
+			Some(LoaderInfo {
+				kind: LoaderKind::Fabric,
+				version: "0.15.0".into()
+			})
+		);
+
+		let neoforge = profile(
+			&["net.neoforged:forge:1.20.1-47.1.106:universal"],
+			&[
+				"--fml.forgeVersion",
+				"47.1.106",
+				"--fml.mcVersion",
+				"1.20.1",
+			],
+		);
+		assert_eq!(
+			detect_loader(&neoforge, "1.20.1").map(|l| l.kind),
+			Some(LoaderKind::NeoForge)
+		);
+
+		let legacy = profile(
+			&["net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10"],
+			&[],
+		);
+		assert_eq!(
+			detect_loader(&legacy, "1.7.10").unwrap().version,
+			"10.13.4.1614-1.7.10"
+		);
+
+		let optifine = profile(&["optifine:OptiFine:1.12.2_HD_U_G5"], &[]);
+		assert_eq!(
+			detect_loader(&optifine, "1.12.2").unwrap().version,
+			"HD_U_G5"
+		);
+		assert!(detect_loader(&profile(&["org.lwjgl:lwjgl:3.3.1"], &[]), "1.20.1").is_none());
+	}
+}
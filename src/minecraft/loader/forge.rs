@@ -0,0 +1,411 @@
+use crate::minecraft::game::maven::MavenCoord;
+use crate::minecraft::loader::fetch_json;
+use crate::minecraft::profile::{Library, VersionProfile};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// 1.13 及以后安装器中的 `install_profile.json`
+#[derive(Debug, Clone, Deserialize)]
+pub struct InstallProfile {
+	pub minecraft: String,
+	/// 安装器内版本 JSON 的路径，通常为 `/version.json`
+	#[serde(default = "default_json")]
+	pub json: String,
+	#[serde(default)]
+	pub data: HashMap<String, DataEntry>,
+	#[serde(default)]
+	pub processors: Vec<Processor>,
+	/// 处理器运行所需的库
+	#[serde(default)]
+	pub libraries: Vec<Library>,
+}
+
+fn default_json() -> String {
+	"/version.json".into()
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DataEntry {
+	pub client: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Processor {
+	/// 不写时客户端与服务端都要运行
+	#[serde(default)]
+	pub sides: Option<Vec<String>>,
+	pub jar: String,
+	#[serde(default)]
+	pub classpath: Vec<String>,
+	#[serde(default)]
+	pub args: Vec<String>,
+	/// 输出文件与期望的 SHA-1，键和值都可以是 `{DATA}` 或 `[coord]`
+	#[serde(default)]
+	pub outputs: HashMap<String, String>,
+}
+
+impl Processor {
+	pub fn runs_on_client(&self) -> bool {
+		self.sides
+			.as_ref()
+			.is_none_or(|sides| sides.iter().any(|s| s == "client"))
+	}
+}
+
+/// 1.5–1.12 安装器的 `install_profile.json`，版本 JSON 内嵌在 `versionInfo` 中
+#[derive(Debug, Clone, Deserialize)]
+struct LegacyInstallProfile {
+	install: LegacyInstall,
+	#[serde(rename = "versionInfo")]
+	version_info: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LegacyInstall {
+	/// universal jar 的 Maven 坐标
+	pub path: String,
+	/// universal jar 在安装器内的文件名
+	#[serde(rename = "filePath")]
+	pub file_path: String,
+	pub minecraft: String,
+}
+
+/// 已下载的 Forge 安装器
+pub struct ForgeInstaller {
+	pub path: PathBuf,
+	pub profile: InstallProfile,
+	pub version: VersionProfile,
+	/// 旧版安装器需要解压的 universal jar
+	pub legacy: Option<LegacyInstall>,
+}
+
+impl ForgeInstaller {
+	pub fn open(path: &Path) -> Result<Self> {
+		let mut archive = open_zip(path)?;
+		let raw: serde_json::Value = serde_json::from_slice(
+			&read_entry(&mut archive, "install_profile.json")
+				.context("installer has no install_profile.json")?,
+		)
+		.context("parse install_profile.json")?;
+		if raw.get("versionInfo").is_some() {
+			return Self::open_legacy(path, raw);
+		}
+
+		let profile: InstallProfile =
+			serde_json::from_value(raw).context("parse install_profile.json")?;
+		let json = profile.json.trim_start_matches('/').to_string();
+		let version: VersionProfile = serde_json::from_slice(&read_entry(&mut archive, &json)?)
+			.with_context(|| format!("parse {json}"))?;
+		Ok(Self {
+			path: path.to_path_buf(),
+			profile,
+			version,
+			legacy: None,
+		})
+	}
+
+	// 旧版没有处理器，只需解压 universal jar 并写入 versionInfo
+	fn open_legacy(path: &Path, raw: serde_json::Value) -> Result<Self> {
+		let mut legacy: LegacyInstallProfile =
+			serde_json::from_value(raw).context("parse legacy install_profile.json")?;
+		// clientreq 为 false 的库只有服务端需要
+		if let Some(libs) = legacy
+			.version_info
+			.get_mut("libraries")
+			.and_then(|l| l.as_array_mut())
+		{
+			libs.retain(|lib| lib.get("clientreq").and_then(|v| v.as_bool()) != Some(false));
+		}
+		let version: VersionProfile =
+			serde_json::from_value(legacy.version_info).context("parse versionInfo")?;
+		Ok(Self {
+			path: path.to_path_buf(),
+			profile: InstallProfile {
+				minecraft: legacy.install.minecraft.clone(),
+				json: String::new(),
+				data: HashMap::new(),
+				processors: Vec::new(),
+				libraries: Vec::new(),
+			},
+			version,
+			legacy: Some(legacy.install),
+		})
+	}
+
+	/// 把安装器自带的 `maven/` 构件（旧版为 universal jar）解压到库目录
+	pub fn extract_maven(&self, libraries_dir: &Path) -> Result<()> {
+		let mut archive = open_zip(&self.path)?;
+		if let Some(legacy) = &self.legacy {
+			let dest = MavenCoord::parse(&legacy.path)?.local_path(libraries_dir);
+			if let Some(parent) = dest.parent() {
+				fs::create_dir_all(parent)?;
+			}
+			fs::write(&dest, read_entry(&mut archive, &legacy.file_path)?)?;
+		}
+		for i in 0..archive.len() {
+			let mut entry = archive.by_index(i)?;
+			let Some(rel) = entry
+				.enclosed_name()
+				.and_then(|p| p.strip_prefix("maven").ok().map(Path::to_path_buf))
+			else {
+				continue;
+			};
+			if entry.is_dir() || rel.as_os_str().is_empty() {
+				continue;
+			}
+			let dest = libraries_dir.join(rel);
+			if let Some(parent) = dest.parent() {
+				fs::create_dir_all(parent)?;
+			}
+			let mut out = fs::File::create(&dest)?;
+			std::io::copy(&mut entry, &mut out)?;
+		}
+		Ok(())
+	}
+
+	/// 构造处理器参数使用的数据表，安装器内的文件（`/data/client.lzma` 等）解压到 `work_dir`
+	pub fn data_map(
+		&self,
+		game_dir: &Path,
+		minecraft_jar: &Path,
+		work_dir: &Path,
+	) -> Result<HashMap<String, String>> {
+		let libraries_dir = game_dir.join("libraries");
+		let mut archive = open_zip(&self.path)?;
+		let mut data = HashMap::new();
+		for (key, entry) in &self.profile.data {
+			let value = &entry.client;
+			let resolved = if let Some(inner) = value.strip_prefix('/') {
+				let dest = work_dir.join(inner);
+				if let Some(parent) = dest.parent() {
+					fs::create_dir_all(parent)?;
+				}
+				fs::write(&dest, read_entry(&mut archive, inner)?)?;
+				path_string(&dest)
+			} else {
+				resolve_value(value, &HashMap::new(), &libraries_dir)?
+			};
+			data.insert(key.clone(), resolved);
+		}
+
+		data.insert("SIDE".into(), "client".into());
+		data.insert("MINECRAFT_JAR".into(), path_string(minecraft_jar));
+		data.insert("MINECRAFT_VERSION".into(), self.profile.minecraft.clone());
+		data.insert("ROOT".into(), path_string(game_dir));
+		data.insert("INSTALLER".into(), path_string(&self.path));
+		data.insert("LIBRARY_DIR".into(), path_string(&libraries_dir));
+		Ok(data)
+	}
+}
+
+/// 解析处理器参数：`{KEY}` 取数据表，`[coord]` 为库路径，`'text'` 为字面量
+pub fn resolve_value(
+	value: &str,
+	data: &HashMap<String, String>,
+	libraries_dir: &Path,
+) -> Result<String> {
+	if let Some(key) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+		return data
+			.get(key)
+			.cloned()
+			.ok_or_else(|| anyhow::anyhow!("Unknown installer data {key}"));
+	}
+	if let Some(coord) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+		return Ok(path_string(
+			&MavenCoord::parse(coord)?.local_path(libraries_dir),
+		));
+	}
+	if let Some(text) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+		return Ok(text.to_string());
+	}
+	Ok(value.to_string())
+}
+
+/// Forge 仓库中的 `promotions_slim.json`
+#[derive(Debug, Deserialize)]
+pub struct Promotions {
+	#[serde(default)]
+	pub promos: HashMap<String, String>,
+}
+
+impl Promotions {
+	/// 优先取推荐构建，没有时取最新构建
+	pub fn pick(&self, game_version: &str) -> Option<&String> {
+		["recommended", "latest"]
+			.iter()
+			.find_map(|tag| self.promos.get(&format!("{game_version}-{tag}")))
+	}
+}
+
+pub async fn promotions(repo: &str) -> Result<Promotions> {
+	fetch_json(&format!(
+		"{}/net/minecraftforge/forge/promotions_slim.json",
+		repo.trim_end_matches('/')
+	))
+	.await
+}
+
+/// 读取处理器 jar 清单中的 `Main-Class`
+pub fn main_class(jar: &Path) -> Result<String> {
+	let mut archive = open_zip(jar)?;
+	let manifest =
+		String::from_utf8_lossy(&read_entry(&mut archive, "META-INF/MANIFEST.MF")?).into_owned();
+	manifest
+		.lines()
+		.find_map(|l| l.strip_prefix("Main-Class:"))
+		.map(|c| c.trim().to_string())
+		.ok_or_else(|| anyhow::anyhow!("{} has no Main-Class", jar.display()))
+}
+
+pub fn file_sha1(path: &Path) -> Result<String> {
+	use sha1::{Digest, Sha1};
+	let data = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+	Ok(hex::encode(Sha1::digest(&data)))
+}
+
+fn open_zip(path: &Path) -> Result<zip::ZipArchive<fs::File>> {
+	let file = fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+	zip::ZipArchive::new(file).with_context(|| format!("read {}", path.display()))
+}
+
+fn read_entry(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Result<Vec<u8>> {
+	let mut entry = archive
+		.by_name(name)
+		.with_context(|| format!("{name} not found in archive"))?;
+	let mut buf = Vec::new();
+	entry.read_to_end(&mut buf)?;
+	Ok(buf)
+}
+
+fn path_string(path: &Path) -> String {
+	path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+
+	#[test]
+	fn test_pick_promotion() {
+		let promotions: Promotions = serde_json::from_str(
+			r#"{"homepage":"https://files.minecraftforge.net/","promos":{
+				"1.20.1-latest":"47.3.0","1.20.1-recommended":"47.2.0","1.21.4-latest":"54.0.6"}}"#,
+		)
+		.unwrap();
+		assert_eq!(promotions.pick("1.20.1").unwrap(), "47.2.0");
+		assert_eq!(promotions.pick("1.21.4").unwrap(), "54.0.6");
+		assert!(promotions.pick("1.14").is_none());
+	}
+
+	#[test]
+	fn test_open_installer() {
+		let dir = tempfile::tempdir().unwrap();
+		let installer = dir.path().join("forge-installer.jar");
+		let mut zip = zip::ZipWriter::new(fs::File::create(&installer).unwrap());
+		let options = zip::write::SimpleFileOptions::default();
+		zip.start_file("install_profile.json", options).unwrap();
+		zip.write_all(
+			br#"{
+				"spec": 1,
+				"version": "1.20.1-forge-47.2.0",
+				"minecraft": "1.20.1",
+				"data": {
+					"MAPPINGS": {"client": "[de.oceanlabs.mcp:mcp_config:1.20.1@zip]", "server": ""},
+					"BINPATCH": {"client": "/data/client.lzma", "server": "/data/server.lzma"},
+					"PATCHED_SHA": {"client": "'abc'", "server": "'def'"}
+				},
+				"processors": [{"sides": ["server"], "jar": "a:b:1", "args": []}]
+			}"#,
+		)
+		.unwrap();
+		zip.start_file("version.json", options).unwrap();
+		zip.write_all(br#"{"id": "1.20.1-forge-47.2.0", "inheritsFrom": "1.20.1"}"#)
+			.unwrap();
+		zip.start_file("data/client.lzma", options).unwrap();
+		zip.write_all(b"patch").unwrap();
+		zip.start_file("maven/net/minecraftforge/forge/47.2.0/forge.jar", options)
+			.unwrap();
+		zip.write_all(b"jar").unwrap();
+		zip.finish().unwrap();
+
+		let installer = ForgeInstaller::open(&installer).unwrap();
+		assert_eq!(installer.version.id, "1.20.1-forge-47.2.0");
+		assert!(!installer.profile.processors[0].runs_on_client());
+
+		let game_dir = dir.path().join("game");
+		installer
+			.extract_maven(&game_dir.join("libraries"))
+			.unwrap();
+		assert!(
+			game_dir
+				.join("libraries/net/minecraftforge/forge/47.2.0/forge.jar")
+				.exists()
+		);
+
+		let work = dir.path().join("work");
+		let data = installer
+			.data_map(&game_dir, Path::new("client.jar"), &work)
+			.unwrap();
+		assert_eq!(data["PATCHED_SHA"], "abc");
+		assert!(data["MAPPINGS"].ends_with("mcp_config-1.20.1.zip"));
+		assert_eq!(fs::read(&data["BINPATCH"]).unwrap(), b"patch");
+		assert_eq!(
+			resolve_value("{SIDE}", &data, &game_dir.join("libraries")).unwrap(),
+			"client"
+		);
+	}
+
+	#[test]
+	fn test_open_legacy_installer() {
+		let dir = tempfile::tempdir().unwrap();
+		let installer = dir.path().join("forge-1.7.10-installer.jar");
+		let mut zip = zip::ZipWriter::new(fs::File::create(&installer).unwrap());
+		let options = zip::write::SimpleFileOptions::default();
+		zip.start_file("install_profile.json", options).unwrap();
+		zip.write_all(
+			br#"{
+				"install": {
+					"path": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10",
+					"filePath": "forge-1.7.10-10.13.4.1614-1.7.10-universal.jar",
+					"version": "1.7.10-Forge10.13.4.1614-1.7.10",
+					"minecraft": "1.7.10"
+				},
+				"versionInfo": {
+					"id": "1.7.10-Forge10.13.4.1614-1.7.10",
+					"mainClass": "net.minecraft.launchwrapper.Launch",
+					"minecraftArguments": "--tweakClass cpw.mods.fml.common.launcher.FMLTweaker",
+					"libraries": [
+						{"name": "net.minecraft:launchwrapper:1.12", "clientreq": true},
+						{"name": "org.scala-lang:scala-library:2.11.1", "url": "http://files.minecraftforge.net/maven/", "clientreq": true},
+						{"name": "com.typesafe:config:1.2.1", "clientreq": false}
+					]
+				}
+			}"#,
+		)
+		.unwrap();
+		zip.start_file("forge-1.7.10-10.13.4.1614-1.7.10-universal.jar", options)
+			.unwrap();
+		zip.write_all(b"universal").unwrap();
+		zip.finish().unwrap();
+
+		let installer = ForgeInstaller::open(&installer).unwrap();
+		assert_eq!(installer.profile.minecraft, "1.7.10");
+		assert!(installer.profile.processors.is_empty());
+		assert_eq!(installer.version.libraries.len(), 2);
+
+		let libraries = dir.path().join("libraries");
+		installer.extract_maven(&libraries).unwrap();
+		assert!(
+			libraries
+				.join(
+					"net/minecraftforge/forge/1.7.10-10.13.4.1614-1.7.10/forge-1.7.10-10.13.4.1614-1.7.10.jar"
+				)
+				.exists()
+		);
+	}
+}
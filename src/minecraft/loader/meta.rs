@@ -0,0 +1,124 @@
+use crate::minecraft::loader::{LoaderKind, fetch_json};
+use crate::minecraft::profile::VersionProfile;
+use anyhow::Result;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoaderVersion {
+	pub version: String,
+	#[serde(default)]
+	pub stable: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LoaderEntry {
+	loader: LoaderVersion,
+}
+
+/// Fabric 与 Quilt 的 meta API，两者结构相同，只是路径版本不同
+#[derive(Debug, Clone)]
+pub struct MetaApi {
+	pub kind: LoaderKind,
+	base: String,
+}
+
+impl MetaApi {
+	pub fn fabric(meta: &str) -> Self {
+		Self {
+			kind: LoaderKind::Fabric,
+			base: format!("{}/v2", meta.trim_end_matches('/')),
+		}
+	}
+
+	pub fn quilt(meta: &str) -> Self {
+		Self {
+			kind: LoaderKind::Quilt,
+			base: format!("{}/v3", meta.trim_end_matches('/')),
+		}
+	}
+
+	/// 与指定游戏版本兼容的加载器版本，按新到旧排列
+	pub async fn loader_versions(&self, game_version: &str) -> Result<Vec<LoaderVersion>> {
+		let url = format!("{}/versions/loader/{game_version}", self.base);
+		let entries: Vec<LoaderEntry> = fetch_json(&url).await?;
+		let mut versions: Vec<LoaderVersion> = entries.into_iter().map(|e| e.loader).collect();
+		// Quilt 不提供 stable 字段，带预发布后缀的视为不稳定
+		if self.kind == LoaderKind::Quilt {
+			for v in &mut versions {
+				v.stable = !v.version.contains('-');
+			}
+		}
+		Ok(versions)
+	}
+
+	/// 加载器的版本 JSON，inheritsFrom 指向原版
+	pub async fn loader_profile(&self, game_version: &str, loader: &str) -> Result<VersionProfile> {
+		let url = format!(
+			"{}/versions/loader/{game_version}/{loader}/profile/json",
+			self.base
+		);
+		fetch_json(&url).await
+	}
+}
+
+/// 指定了版本时必须在兼容列表里，否则取最新的稳定版
+pub fn pick_loader<'a>(
+	versions: &'a [LoaderVersion],
+	wanted: Option<&str>,
+) -> Option<&'a LoaderVersion> {
+	match wanted {
+		Some(v) => versions.iter().find(|l| l.version == v),
+		None => versions
+			.iter()
+			.find(|l| l.stable)
+			.or_else(|| versions.first()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::test_util::{request_path, serve};
+
+	#[tokio::test]
+	async fn test_meta_api() {
+		let url = serve(|req| {
+			let body = match request_path(req) {
+				"/v2/versions/loader/1.20.1/0.15.0/profile/json" => {
+					r#"{"id":"fabric-loader-0.15.0-1.20.1","inheritsFrom":"1.20.1","mainClass":"net.fabricmc.loader.impl.launch.knot.KnotClient","libraries":[{"name":"net.fabricmc:fabric-loader:0.15.0","url":"https://maven.fabricmc.net/"}]}"#
+				}
+				"/v2/versions/loader/1.20.1" => {
+					r#"[{"loader":{"version":"0.15.1-beta","stable":false}},{"loader":{"version":"0.15.0","stable":true}}]"#
+				}
+				"/v3/versions/loader/1.20.1" => {
+					r#"[{"loader":{"version":"0.23.0-beta.1"}},{"loader":{"version":"0.22.0"}}]"#
+				}
+				_ => "[]",
+			};
+			("200 OK", body.to_string())
+		})
+		.await;
+
+		let fabric = MetaApi::fabric(&url);
+		let versions = fabric.loader_versions("1.20.1").await.unwrap();
+		let loader = pick_loader(&versions, None).unwrap();
+		assert_eq!(loader.version, "0.15.0");
+		assert!(pick_loader(&versions, Some("0.1.0")).is_none());
+
+		let profile = fabric
+			.loader_profile("1.20.1", &loader.version)
+			.await
+			.unwrap();
+		assert_eq!(profile.inherits_from.as_deref(), Some("1.20.1"));
+		assert_eq!(
+			profile.libraries[0].url.as_deref(),
+			Some("https://maven.fabricmc.net/")
+		);
+
+		let versions = MetaApi::quilt(&url)
+			.loader_versions("1.20.1")
+			.await
+			.unwrap();
+		assert_eq!(pick_loader(&versions, None).unwrap().version, "0.22.0");
+	}
+}
@@ -0,0 +1,58 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod detect;
+pub mod forge;
+pub mod meta;
+pub mod neoforge;
+pub mod optifine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoaderKind {
+	Fabric,
+	Quilt,
+	Forge,
+	#[serde(rename = "neoforge")]
+	NeoForge,
+	#[serde(rename = "optifine")]
+	OptiFine,
+}
+
+impl fmt::Display for LoaderKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			LoaderKind::Fabric => "Fabric",
+			LoaderKind::Quilt => "Quilt",
+			LoaderKind::Forge => "Forge",
+			LoaderKind::NeoForge => "NeoForge",
+			LoaderKind::OptiFine => "OptiFine",
+		})
+	}
+}
+
+/// 实例安装的加载器及其版本
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoaderInfo {
+	pub kind: LoaderKind,
+	pub version: String,
+}
+
+pub(crate) async fn fetch_text(url: &str) -> Result<String> {
+	let resp = reqwest::get(url)
+		.await
+		.with_context(|| format!("fetch {url}"))?;
+	let status = resp.status();
+	let text = resp.text().await.with_context(|| format!("read {url}"))?;
+	if !status.is_success() {
+		return Err(anyhow::anyhow!("{url} returned {status}"));
+	}
+	Ok(text)
+}
+
+pub(crate) async fn fetch_json<T: DeserializeOwned>(url: &str) -> Result<T> {
+	let text = fetch_text(url).await?;
+	serde_json::from_str(&text).with_context(|| format!("parse {url}"))
+}
@@ -0,0 +1,106 @@
+use crate::minecraft::game::maven::MavenCoord;
+use crate::minecraft::loader::fetch_text;
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static VERSION_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<version>([^<]+)</version>").unwrap());
+
+/// 1.20.1 的 NeoForge 沿用 Forge 的坐标与版本号
+const LEGACY_GAME_VERSION: &str = "1.20.1";
+
+fn artifact(game_version: &str) -> &'static str {
+	if game_version == LEGACY_GAME_VERSION {
+		"forge"
+	} else {
+		"neoforge"
+	}
+}
+
+// NeoForge 版本号去掉游戏版本开头的 `1.`：1.21.1 -> 21.1.x，1.21 -> 21.0.x
+fn version_prefix(game_version: &str) -> Option<String> {
+	if game_version == LEGACY_GAME_VERSION {
+		return Some(format!("{LEGACY_GAME_VERSION}-"));
+	}
+	let rest = game_version.strip_prefix("1.")?;
+	Some(match rest.split_once('.') {
+		Some((major, minor)) => format!("{major}.{minor}."),
+		None => format!("{rest}.0."),
+	})
+}
+
+/// 从 maven-metadata.xml 中取出适用于该游戏版本的构建，按新到旧排列
+pub fn parse_metadata(xml: &str, game_version: &str) -> Vec<String> {
+	let Some(prefix) = version_prefix(game_version) else {
+		return Vec::new();
+	};
+	let mut versions: Vec<String> = VERSION_RE
+		.captures_iter(xml)
+		.map(|c| c[1].to_string())
+		.filter(|v| v.starts_with(&prefix))
+		.map(|v| match game_version {
+			LEGACY_GAME_VERSION => v[prefix.len()..].to_string(),
+			_ => v,
+		})
+		.collect();
+	versions.reverse();
+	versions
+}
+
+pub async fn neoforge_versions(repo: &str, game_version: &str) -> Result<Vec<String>> {
+	let url = format!(
+		"{}/net/neoforged/{}/maven-metadata.xml",
+		repo.trim_end_matches('/'),
+		artifact(game_version)
+	);
+	Ok(parse_metadata(&fetch_text(&url).await?, game_version))
+}
+
+/// 不指定版本时取最新的非 beta 构建
+pub fn pick_version<'a>(versions: &'a [String], wanted: Option<&str>) -> Option<&'a String> {
+	match wanted {
+		Some(v) => versions.iter().find(|b| *b == v),
+		None => versions
+			.iter()
+			.find(|v| !v.contains("beta"))
+			.or_else(|| versions.first()),
+	}
+}
+
+pub fn installer_coord(game_version: &str, version: &str) -> Result<MavenCoord> {
+	let full = match game_version {
+		LEGACY_GAME_VERSION => format!("{LEGACY_GAME_VERSION}-{version}"),
+		_ => version.to_string(),
+	};
+	MavenCoord::parse(&format!(
+		"net.neoforged:{}:{full}:installer",
+		artifact(game_version)
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_metadata() {
+		let xml = r#"<metadata><versioning><versions>
+			<version>21.0.167</version>
+			<version>21.1.1-beta</version>
+			<version>21.1.77</version>
+		</versions></versioning></metadata>"#;
+		assert_eq!(parse_metadata(xml, "1.21.1"), ["21.1.77", "21.1.1-beta"]);
+		assert_eq!(parse_metadata(xml, "1.21"), ["21.0.167"]);
+		assert_eq!(
+			pick_version(&parse_metadata(xml, "1.21.1"), None).unwrap(),
+			"21.1.77"
+		);
+
+		let legacy = "<version>1.20.1-47.1.106</version>";
+		assert_eq!(parse_metadata(legacy, "1.20.1"), ["47.1.106"]);
+		assert_eq!(
+			installer_coord("1.20.1", "47.1.106").unwrap().file_name(),
+			"forge-1.20.1-47.1.106-installer.jar"
+		);
+	}
+}
@@ -0,0 +1,192 @@
+use crate::minecraft::loader::fetch_json;
+use crate::minecraft::profile::{ArgumentValue, Arguments, Library, VersionProfile};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const TWEAK_CLASS: &str = "optifine.OptiFineTweaker";
+const LAUNCH_WRAPPER: &str = "net.minecraft:launchwrapper:1.12";
+
+/// 下载源提供的 OptiFine 构建信息（BMCLAPI 格式）
+#[derive(Debug, Clone, Deserialize)]
+pub struct OptiFineBuild {
+	#[serde(rename = "mcversion")]
+	pub game_version: String,
+	/// 如 `HD_U`
+	#[serde(rename = "type")]
+	pub edition: String,
+	/// 如 `I6`，预览版带 `pre` 前缀
+	pub patch: String,
+	pub filename: String,
+	/// 兼容的 Forge 版本，如 `Forge 47.1.0`，不兼容时为 `Forge N/A`
+	#[serde(default)]
+	pub forge: Option<String>,
+}
+
+impl OptiFineBuild {
+	/// 如 `HD_U_I6`
+	pub fn name(&self) -> String {
+		format!("{}_{}", self.edition, self.patch)
+	}
+
+	pub fn is_preview(&self) -> bool {
+		self.filename.starts_with("preview_") || self.patch.starts_with("pre")
+	}
+
+	pub fn supports_forge(&self) -> bool {
+		self.forge.as_deref().is_some_and(|f| !f.contains("N/A"))
+	}
+
+	/// 作为库时使用的坐标
+	pub fn library_name(&self) -> String {
+		format!("optifine:OptiFine:{}_{}", self.game_version, self.name())
+	}
+
+	pub fn download_url(&self, source: &str) -> String {
+		format!(
+			"{}/optifine/{}/{}/{}",
+			source.trim_end_matches('/'),
+			self.game_version,
+			self.edition,
+			self.patch
+		)
+	}
+}
+
+/// 适用于指定游戏版本的全部构建
+pub async fn builds(source: &str, game_version: &str) -> Result<Vec<OptiFineBuild>> {
+	let url = format!("{}/optifine/{game_version}", source.trim_end_matches('/'));
+	let builds: Vec<OptiFineBuild> = fetch_json(&url).await?;
+	Ok(builds
+		.into_iter()
+		.filter(|b| b.game_version == game_version)
+		.collect())
+}
+
+/// 指定了名称时按名称查找，否则取列表中最后一个正式版（下载源按发布时间排列）
+pub fn pick_build<'a>(
+	builds: &'a [OptiFineBuild],
+	wanted: Option<&str>,
+) -> Option<&'a OptiFineBuild> {
+	match wanted {
+		Some(name) => builds.iter().find(|b| b.name() == name),
+		None => builds
+			.iter()
+			.rev()
+			.find(|b| !b.is_preview())
+			.or_else(|| builds.last()),
+	}
+}
+
+/// 新版安装器需要用 `optifine.Patcher` 对照原版客户端生成库，旧版安装器本身就是库
+pub fn has_patcher(installer: &Path) -> Result<bool> {
+	let mut archive = open_zip(installer)?;
+	Ok(archive.by_name("optifine/Patcher.class").is_ok())
+}
+
+/// 安装器内置的 LaunchWrapper（1.13 以后的版本需要），返回版本与 jar 内容
+pub fn bundled_launchwrapper(installer: &Path) -> Result<Option<(String, Vec<u8>)>> {
+	let mut archive = open_zip(installer)?;
+	let version = match archive.by_name("launchwrapper-of.txt") {
+		Ok(mut entry) => {
+			let mut text = String::new();
+			entry.read_to_string(&mut text)?;
+			text.trim().to_string()
+		}
+		Err(_) => return Ok(None),
+	};
+	let mut jar = Vec::new();
+	archive
+		.by_name(&format!("launchwrapper-of-{version}.jar"))
+		.context("launchwrapper-of jar missing from installer")?
+		.read_to_end(&mut jar)?;
+	Ok(Some((version, jar)))
+}
+
+/// 独立版本的 JSON：以原版为父版本，通过 LaunchWrapper 加载 OptiFine
+pub fn standalone_profile(
+	build: &OptiFineBuild,
+	id: &str,
+	parent: &VersionProfile,
+	launchwrapper: Option<&str>,
+) -> VersionProfile {
+	let launchwrapper = launchwrapper.unwrap_or(LAUNCH_WRAPPER);
+	let mut profile = VersionProfile {
+		id: id.to_string(),
+		inherits_from: Some(build.game_version.clone()),
+		jar: Some(build.game_version.clone()),
+		main_class: Some("net.minecraft.launchwrapper.Launch".into()),
+		libraries: vec![
+			Library {
+				name: build.library_name(),
+				..Default::default()
+			},
+			Library {
+				name: launchwrapper.to_string(),
+				..Default::default()
+			},
+		],
+		..Default::default()
+	};
+	// minecraftArguments 会整体覆盖父版本，arguments 则是追加
+	match &parent.minecraft_arguments {
+		Some(args) if parent.arguments.is_none() => {
+			profile.minecraft_arguments = Some(format!("{args} --tweakClass {TWEAK_CLASS}"));
+		}
+		_ => {
+			profile.arguments = Some(Arguments {
+				game: vec![
+					ArgumentValue::Plain("--tweakClass".into()),
+					ArgumentValue::Plain(TWEAK_CLASS.into()),
+				],
+				jvm: Vec::new(),
+			});
+		}
+	}
+	profile
+}
+
+fn open_zip(path: &Path) -> Result<zip::ZipArchive<fs::File>> {
+	let file = fs::File::open(path).with_context(|| format!("open {}", path.display()))?;
+	zip::ZipArchive::new(file).with_context(|| format!("read {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn build(patch: &str) -> OptiFineBuild {
+		OptiFineBuild {
+			game_version: "1.12.2".into(),
+			edition: "HD_U".into(),
+			patch: patch.into(),
+			filename: format!("OptiFine_1.12.2_HD_U_{patch}.jar"),
+			forge: Some("Forge N/A".into()),
+		}
+	}
+
+	#[test]
+	fn test_standalone_profile() {
+		let builds = [build("G5"), build("pre1")];
+		let picked = pick_build(&builds, None).unwrap();
+		assert_eq!(picked.name(), "HD_U_G5");
+		assert!(!picked.supports_forge());
+
+		let parent = VersionProfile {
+			minecraft_arguments: Some("--username ${auth_player_name}".into()),
+			..Default::default()
+		};
+		let profile = standalone_profile(picked, "1.12.2-OptiFine_HD_U_G5", &parent, None);
+		assert_eq!(
+			profile.minecraft_arguments.as_deref(),
+			Some("--username ${auth_player_name} --tweakClass optifine.OptiFineTweaker")
+		);
+		assert_eq!(
+			profile.libraries[0].name,
+			"optifine:OptiFine:1.12.2_HD_U_G5"
+		);
+		assert_eq!(profile.libraries[1].name, LAUNCH_WRAPPER);
+	}
+}
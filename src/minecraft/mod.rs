@@ -1,4 +1,5 @@
 pub mod account;
 pub mod game;
+pub mod loader;
 pub mod profile;
 pub mod tasks;
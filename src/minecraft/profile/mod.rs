@@ -1,30 +1,139 @@
+use crate::minecraft::game::java::JavaRequirement;
+use crate::minecraft::game::maven::MavenCoord;
 use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct VersionProfile {
-	#[serde(default, rename = "inheritsFrom")]
+	#[serde(default)]
+	pub id: String,
+	/// release / snapshot / old_beta / old_alpha
+	#[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+	pub version_type: Option<String>,
+	#[serde(
+		default,
+		rename = "releaseTime",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub release_time: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub time: Option<String>,
+	/// 使用其他版本的客户端 jar，常见于加载器生成的版本
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub jar: Option<String>,
+	#[serde(
+		default,
+		rename = "inheritsFrom",
+		skip_serializing_if = "Option::is_none"
+	)]
 	pub inherits_from: Option<String>,
-	#[serde(default, rename = "mainClass")]
+	#[serde(default, rename = "mainClass", skip_serializing_if = "Option::is_none")]
 	pub main_class: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub arguments: Option<Arguments>,
-	#[serde(default, rename = "minecraftArguments")]
+	#[serde(
+		default,
+		rename = "minecraftArguments",
+		skip_serializing_if = "Option::is_none"
+	)]
 	pub minecraft_arguments: Option<String>,
 	#[serde(default)]
 	pub libraries: Vec<Library>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub assets: Option<String>,
-	#[serde(default, rename = "assetIndex")]
+	#[serde(
+		default,
+		rename = "assetIndex",
+		skip_serializing_if = "Option::is_none"
+	)]
 	pub asset_index: Option<AssetIndexInfo>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub downloads: Option<VersionDownloads>,
+	#[serde(
+		default,
+		rename = "javaVersion",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub java_version: Option<JavaVersion>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub logging: Option<Logging>,
+	#[serde(
+		default,
+		rename = "complianceLevel",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub compliance_level: Option<u32>,
+	#[serde(
+		default,
+		rename = "minimumLauncherVersion",
+		skip_serializing_if = "Option::is_none"
+	)]
+	pub minimum_launcher_version: Option<u32>,
+	/// 未建模的字段，写回时原样保留
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+impl VersionProfile {
+	/// 需要的 Java 主版本，旧版本清单没有 javaVersion，均为 Java 8
+	pub fn required_java(&self) -> u32 {
+		self.java_version.as_ref().map_or(8, |j| j.major_version)
+	}
+
+	/// 启动时对 Java 的要求，区分 javaVersion 是否明确给出
+	pub fn java_requirement(&self) -> JavaRequirement {
+		JavaRequirement {
+			major: self.required_java(),
+			explicit: self.java_version.is_some(),
+		}
+	}
+
+	/// 客户端 jar 所在的版本目录，未指定 jar 时就是版本本身
+	pub fn jar_id<'a>(&'a self, version: &'a str) -> &'a str {
+		self.jar.as_deref().unwrap_or(version)
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JavaVersion {
+	/// Mojang 运行时组件名，如 `java-runtime-delta`
+	#[serde(default)]
+	pub component: String,
+	#[serde(rename = "majorVersion")]
+	pub major_version: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct Logging {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client: Option<LoggingConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoggingConfig {
+	/// 如 `-Dlog4j.configurationFile=${path}`
+	pub argument: String,
+	pub file: LoggingFile,
+	#[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+	pub config_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
+pub struct LoggingFile {
+	pub id: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sha1: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub size: Option<u64>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Arguments {
 	#[serde(default)]
 	pub game: Vec<ArgumentValue>,
@@ -32,22 +141,22 @@ pub struct Arguments {
 	pub jvm: Vec<ArgumentValue>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum ArgumentValue {
 	Plain(String),
 	Obj(ArgObj),
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ArgObj {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub rules: Option<Vec<Rule>>,
 	#[serde(default)]
 	pub value: ArgValueInner,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
 pub enum ArgValueInner {
 	One(String),
@@ -60,113 +169,220 @@ impl Default for ArgValueInner {
 	}
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Library {
 	pub name: String,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub natives: Option<HashMap<String, String>>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub rules: Option<Vec<Rule>>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub downloads: Option<LibraryDownloads>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub extract: Option<Extract>,
+	/// Maven 仓库地址，Forge、Fabric 等的库只给出这一项
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub url: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub sha1: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub size: Option<u64>,
+	/// 未建模的字段（如旧版 Forge 的 clientreq、checksums），写回时原样保留
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Extract {
 	#[serde(default)]
 	pub exclude: Vec<String>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Rule {
 	pub action: String,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub os: Option<RuleOs>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub features: Option<HashMap<String, bool>>,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct RuleOs {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub name: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub version: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub arch: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct LibraryDownloads {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub artifact: Option<Artifact>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub classifiers: Option<HashMap<String, Artifact>>,
+	#[serde(flatten)]
+	pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct Artifact {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub path: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub url: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub sha1: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub size: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct AssetIndexInfo {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub id: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub sha1: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub size: Option<u64>,
-	#[serde(default, rename = "totalSize")]
+	#[serde(default, rename = "totalSize", skip_serializing_if = "Option::is_none")]
 	pub total_size: Option<u64>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub url: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct VersionDownloads {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub client: Option<DownloadEntry>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub server: Option<DownloadEntry>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_mappings: Option<DownloadEntry>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub server_mappings: Option<DownloadEntry>,
 }
 
-#[derive(Debug, Deserialize, Default, Clone)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct DownloadEntry {
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub sha1: Option<String>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub size: Option<u64>,
-	#[serde(default)]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
 	pub url: Option<String>,
 }
 
+/// inheritsFrom 链允许的最大层数
+pub const MAX_INHERIT_DEPTH: usize = 8;
+
+/// 合并 inheritsFrom 链时被子版本替换掉的库
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+	pub overridden: Vec<LibraryOverride>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LibraryOverride {
+	/// 父版本中的库坐标
+	pub parent: String,
+	/// 替换它的子版本库坐标
+	pub child: String,
+}
+
 pub fn load_version_profile(game_dir: &Path, version: &str) -> Result<VersionProfile> {
+	load_version_profile_with_report(game_dir, version).map(|(profile, _)| profile)
+}
+
+pub fn load_version_profile_with_report(
+	game_dir: &Path,
+	version: &str,
+) -> Result<(VersionProfile, MergeReport)> {
+	let (_, mut profiles) = read_chain(game_dir, version)?;
+
+	// 从最顶层的父版本开始逐层合并
+	let mut report = MergeReport::default();
+	let mut profile = profiles.pop().unwrap_or_default();
+	while let Some(child) = profiles.pop() {
+		profile = merge_profile_with_report(profile, child, &mut report);
+	}
+	Ok((profile, report))
+}
+
+/// inheritsFrom 链最顶层的版本名，没有父版本时为自身
+pub fn root_version(game_dir: &Path, version: &str) -> Result<String> {
+	let (mut chain, _) = read_chain(game_dir, version)?;
+	Ok(chain.pop().unwrap_or_else(|| version.to_string()))
+}
+
+// 依次读取自身及各层父版本，返回的 profile 已取走 inheritsFrom
+fn read_chain(game_dir: &Path, version: &str) -> Result<(Vec<String>, Vec<VersionProfile>)> {
+	let mut chain = vec![version.to_string()];
+	let mut profiles = vec![read_version_profile(game_dir, version)?];
+
+	while let Some(parent) = profiles.last_mut().and_then(|p| p.inherits_from.take()) {
+		if chain.contains(&parent) {
+			return Err(anyhow::anyhow!(
+				"inheritsFrom cycle: {} -> {parent}",
+				chain.join(" -> ")
+			));
+		}
+		if chain.len() >= MAX_INHERIT_DEPTH {
+			return Err(anyhow::anyhow!(
+				"inheritsFrom chain of {version} exceeds {MAX_INHERIT_DEPTH} levels"
+			));
+		}
+		profiles.push(read_version_profile(game_dir, &parent)?);
+		chain.push(parent);
+	}
+	Ok((chain, profiles))
+}
+
+fn read_version_profile(game_dir: &Path, version: &str) -> Result<VersionProfile> {
 	let path = game_dir
 		.join("versions")
 		.join(version)
 		.join(format!("{version}.json"));
 	let content = fs::read_to_string(&path)
 		.with_context(|| format!("Read version json failed: {}", path.display()))?;
-	let mut profile: VersionProfile =
-		serde_json::from_str(&content).context("Parse version json failed")?;
-
-	if let Some(parent) = profile.inherits_from.take() {
-		let parent_profile = load_version_profile(game_dir, &parent)?;
-		profile = merge_profile(parent_profile, profile);
-	}
+	serde_json::from_str(&content).context("Parse version json failed")
+}
 
-	Ok(profile)
+/// 写回 `versions/<version>/<version>.json`，不展开 inheritsFrom
+pub fn save_version_profile(
+	game_dir: &Path,
+	version: &str,
+	profile: &VersionProfile,
+) -> Result<()> {
+	let dir = game_dir.join("versions").join(version);
+	fs::create_dir_all(&dir)?;
+	let path = dir.join(format!("{version}.json"));
+	let content = serde_json::to_string_pretty(profile).context("Serialize version json failed")?;
+	fs::write(&path, content)
+		.with_context(|| format!("Write version json failed: {}", path.display()))
 }
 
-pub fn merge_profile(mut base: VersionProfile, child: VersionProfile) -> VersionProfile {
+/// 同一 `group:artifact[:classifier]` 的库只保留一份，子版本优先
+pub fn merge_profile_with_report(
+	mut base: VersionProfile,
+	child: VersionProfile,
+	report: &mut MergeReport,
+) -> VersionProfile {
+	base.id = child.id;
+	if child.version_type.is_some() {
+		base.version_type = child.version_type;
+	}
+	if child.release_time.is_some() {
+		base.release_time = child.release_time;
+	}
+	if child.time.is_some() {
+		base.time = child.time;
+	}
+	if child.jar.is_some() {
+		base.jar = child.jar;
+	}
 	if let Some(mc) = child.main_class {
 		base.main_class = Some(mc);
 	}
@@ -181,6 +397,24 @@ pub fn merge_profile(mut base: VersionProfile, child: VersionProfile) -> Version
 	if let Some(ma) = child.minecraft_arguments {
 		base.minecraft_arguments = Some(ma);
 	}
+	let child_libs: HashMap<String, &str> = child
+		.libraries
+		.iter()
+		.map(|lib| (library_key(lib), lib.name.as_str()))
+		.collect();
+	base.libraries
+		.retain(|lib| match child_libs.get(&library_key(lib)) {
+			Some(name) => {
+				if lib.name != *name {
+					report.overridden.push(LibraryOverride {
+						parent: lib.name.clone(),
+						child: name.to_string(),
+					});
+				}
+				false
+			}
+			None => true,
+		});
 	base.libraries.extend(child.libraries);
 	if let Some(assets) = child.assets {
 		base.assets = Some(assets);
@@ -191,5 +425,169 @@ pub fn merge_profile(mut base: VersionProfile, child: VersionProfile) -> Version
 	if child.downloads.is_some() {
 		base.downloads = child.downloads;
 	}
+	if child.java_version.is_some() {
+		base.java_version = child.java_version;
+	}
+	if child.logging.is_some() {
+		base.logging = child.logging;
+	}
+	if child.compliance_level.is_some() {
+		base.compliance_level = child.compliance_level;
+	}
+	if child.minimum_launcher_version.is_some() {
+		base.minimum_launcher_version = child.minimum_launcher_version;
+	}
+	base.extra.extend(child.extra);
 	base
 }
+
+/// 两份库列表之间的差异，按去掉版本号的坐标对应
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryChange {
+	/// 变更前的库坐标，新增的库为 None
+	pub old: Option<String>,
+	/// 变更后的库坐标，移除的库为 None
+	pub new: Option<String>,
+}
+
+pub fn diff_libraries(old: &[Library], new: &[Library]) -> Vec<LibraryChange> {
+	let old_names: HashMap<String, &str> = old
+		.iter()
+		.map(|lib| (library_key(lib), lib.name.as_str()))
+		.collect();
+	let new_keys: HashSet<String> = new.iter().map(library_key).collect();
+	let mut changes: Vec<LibraryChange> = new
+		.iter()
+		.filter_map(|lib| match old_names.get(&library_key(lib)) {
+			Some(name) if *name == lib.name => None,
+			name => Some(LibraryChange {
+				old: name.map(|n| n.to_string()),
+				new: Some(lib.name.clone()),
+			}),
+		})
+		.collect();
+	changes.extend(
+		old.iter()
+			.filter(|lib| !new_keys.contains(&library_key(lib)))
+			.map(|lib| LibraryChange {
+				old: Some(lib.name.clone()),
+				new: None,
+			}),
+	);
+	changes
+}
+
+// 去掉版本号的坐标，带 natives 的库单独成组，避免与同名普通库互相覆盖
+fn library_key(lib: &Library) -> String {
+	let mut key = match MavenCoord::parse(&lib.name) {
+		Ok(coord) => match coord.classifier {
+			Some(c) => format!("{}:{}:{c}", coord.group, coord.artifact),
+			None => format!("{}:{}", coord.group, coord.artifact),
+		},
+		Err(_) => lib.name.clone(),
+	};
+	if lib.natives.is_some() {
+		key.push_str(":natives");
+	}
+	key
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_round_trip() {
+		let json = r#"{
+			"id": "1.20.1",
+			"type": "release",
+			"releaseTime": "2023-06-12T13:25:51+00:00",
+			"mainClass": "net.minecraft.client.main.Main",
+			"complianceLevel": 1,
+			"minimumLauncherVersion": 21,
+			"javaVersion": {"component": "java-runtime-gamma", "majorVersion": 17},
+			"downloads": {
+				"client": {"sha1": "aa", "size": 1, "url": "https://c"},
+				"client_mappings": {"sha1": "bb", "size": 2, "url": "https://m"}
+			},
+			"logging": {"client": {
+				"argument": "-Dlog4j.configurationFile=${path}",
+				"file": {"id": "client-1.12.xml", "sha1": "cc", "size": 3, "url": "https://l"},
+				"type": "log4j2-xml"
+			}},
+			"libraries": [
+				{"name": "net.fabricmc:fabric-loader:0.14.21", "url": "https://maven.fabricmc.net/"},
+				{"name": "net.minecraftforge:forge:1.7.10-10.13.4.1614-1.7.10", "clientreq": true,
+					"checksums": ["dd"], "downloads": {"vendor": {"mirror": "https://m"}}}
+			],
+			"hakoUnknown": {"keep": true}
+		}"#;
+		let profile: VersionProfile = serde_json::from_str(json).unwrap();
+		assert_eq!(profile.version_type.as_deref(), Some("release"));
+		assert_eq!(profile.minimum_launcher_version, Some(21));
+		assert_eq!(
+			profile.libraries[0].url.as_deref(),
+			Some("https://maven.fabricmc.net/")
+		);
+		assert_eq!(profile.libraries[1].extra["clientreq"], Value::Bool(true));
+
+		let dir = tempfile::tempdir().unwrap();
+		save_version_profile(dir.path(), "1.20.1", &profile).unwrap();
+		let reloaded = load_version_profile(dir.path(), "1.20.1").unwrap();
+		let a = serde_json::to_value(&profile).unwrap();
+		let b = serde_json::to_value(&reloaded).unwrap();
+		assert_eq!(a, b);
+		assert_eq!(a, serde_json::from_str::<Value>(json).unwrap());
+	}
+
+	fn write_version(dir: &Path, id: &str, json: &str) {
+		let path = dir.join("versions").join(id);
+		fs::create_dir_all(&path).unwrap();
+		fs::write(path.join(format!("{id}.json")), json).unwrap();
+	}
+
+	#[test]
+	fn test_inherits_from() {
+		let dir = tempfile::tempdir().unwrap();
+		write_version(
+			dir.path(),
+			"1.20.1",
+			r#"{"id": "1.20.1", "libraries": [
+				{"name": "org.ow2.asm:asm:9.3"},
+				{"name": "com.google.guava:guava:31.1-jre"}
+			]}"#,
+		);
+		write_version(
+			dir.path(),
+			"fabric",
+			r#"{"id": "fabric", "inheritsFrom": "1.20.1", "libraries": [
+				{"name": "org.ow2.asm:asm:9.5", "url": "https://maven.fabricmc.net/"}
+			]}"#,
+		);
+		let (profile, report) = load_version_profile_with_report(dir.path(), "fabric").unwrap();
+		let names: Vec<_> = profile.libraries.iter().map(|l| l.name.as_str()).collect();
+		assert_eq!(
+			names,
+			["com.google.guava:guava:31.1-jre", "org.ow2.asm:asm:9.5"]
+		);
+		assert_eq!(report.overridden.len(), 1);
+		assert_eq!(report.overridden[0].parent, "org.ow2.asm:asm:9.3");
+
+		write_version(dir.path(), "a", r#"{"id": "a", "inheritsFrom": "b"}"#);
+		write_version(dir.path(), "b", r#"{"id": "b", "inheritsFrom": "a"}"#);
+		let err = load_version_profile(dir.path(), "a").unwrap_err();
+		assert!(err.to_string().contains("cycle"));
+
+		// level0 <- level1 <- ... 正好 MAX_INHERIT_DEPTH 层可以加载，再多一层报错
+		write_version(dir.path(), "level0", r#"{"id": "level0"}"#);
+		for i in 1..=MAX_INHERIT_DEPTH {
+			let json = format!(r#"{{"id": "level{i}", "inheritsFrom": "level{}"}}"#, i - 1);
+			write_version(dir.path(), &format!("level{i}"), &json);
+		}
+		let top = format!("level{}", MAX_INHERIT_DEPTH - 1);
+		assert!(load_version_profile(dir.path(), &top).is_ok());
+		let top = format!("level{MAX_INHERIT_DEPTH}");
+		let err = load_version_profile(dir.path(), &top).unwrap_err();
+		assert!(err.to_string().contains("exceeds"));
+	}
+}
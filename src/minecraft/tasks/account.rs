@@ -0,0 +1,399 @@
+use crate::launcher::core::state::AppState;
+use crate::launcher::task::error::{TaskError, TaskResult};
+use crate::launcher::task::framework::{ConcurrentTask, TaskContext, TaskType};
+use crate::launcher::task::lock::LockKey;
+use crate::minecraft::account::offline::{OfflineUuid, PlayerdataMigration, migrate_playerdata};
+use crate::minecraft::account::skin::SkinModel;
+use crate::minecraft::account::{Account, AccountId, AccountManager, SessionState};
+use crate::minecraft::tasks::download::ProgressRef;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use uuid::Uuid;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub struct RefreshAccountTask {
+	pub account: AccountId,
+	pub force: bool,
+}
+
+impl TaskType for RefreshAccountTask {
+	const TYPE_NAME: &'static str = "refresh_account";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for RefreshAccountTask {
+	type Output = SessionState;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource(
+			"refresh_account",
+			self.account.to_string(),
+		)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		AppState::get()
+			.accounts
+			.refresh(self.account, self.force)
+			.await
+			.map_err(|e| TaskError::Failed(format!("refresh account: {e}")))
+	}
+}
+
+/// 微软设备码登录，验证码写入进度信息，在任务列表中展示
+pub struct LoginMicrosoftTask {
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for LoginMicrosoftTask {
+	const TYPE_NAME: &'static str = "login_microsoft";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for LoginMicrosoftTask {
+	type Output = AccountId;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("login_account", "microsoft")]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let state = AppState::get();
+		let client_id = state.config.get().microsoft_client_id;
+		if client_id.is_empty() {
+			return Err(TaskError::Failed(
+				"microsoft_client_id is not configured".to_string(),
+			));
+		}
+		let progress = self.progress.clone();
+		let login = state.accounts.login_microsoft(&client_id, |code| {
+			let message = format!("在 {} 输入代码 {}", code.verification_uri, code.user_code);
+			tracing::info!("{}", message);
+			if let Some(p) = &progress
+				&& let Ok(mut guard) = p.try_write()
+			{
+				guard.message = message;
+			}
+		});
+		// 轮询会一直等到验证码过期，取消时直接放弃
+		let mut cancelled = ctx.cancelled_receiver();
+		let id = tokio::select! {
+			id = login => id.map_err(|e| TaskError::Failed(format!("microsoft login: {e}")))?,
+			_ = cancelled.changed() => return Err(TaskError::Cancelled),
+		};
+
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = match state.accounts.get(id) {
+				Some(account) => format!("已登录 {}", account.username()),
+				None => "登录完成".to_string(),
+			};
+			guard.finished = true;
+		}
+		Ok(id)
+	}
+}
+
+/// 登录 Yggdrasil 兼容的第三方认证服务器
+pub struct LoginYggdrasilTask {
+	/// API 根或支持 ALI 的网站地址
+	pub server: String,
+	pub username: String,
+	pub password: String,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for LoginYggdrasilTask {
+	const TYPE_NAME: &'static str = "login_yggdrasil";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for LoginYggdrasilTask {
+	type Output = AccountId;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("login_account", &self.server)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		if let Some(p) = &self.progress {
+			p.write().await.message = format!("登录 {}", self.server);
+		}
+		let accounts = &AppState::get().accounts;
+		let id = accounts
+			.login_yggdrasil(&self.server, &self.username, &self.password)
+			.await
+			.map_err(|e| TaskError::Failed(format!("yggdrasil login: {e}")))?;
+
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = match accounts.get(id) {
+				Some(account) => format!("已登录 {}", account.username()),
+				None => "登录完成".to_string(),
+			};
+			guard.finished = true;
+		}
+		Ok(id)
+	}
+}
+
+/// 下载账户当前的皮肤并生成头像
+pub struct SyncSkinTask {
+	pub account: AccountId,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for SyncSkinTask {
+	const TYPE_NAME: &'static str = "sync_skin";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for SyncSkinTask {
+	type Output = Option<PathBuf>;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("account_skin", self.account.to_string())]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		if let Some(p) = &self.progress {
+			p.write().await.message = "同步皮肤".to_string();
+		}
+		let skin = AppState::get()
+			.accounts
+			.sync_skin(self.account)
+			.await
+			.map_err(|e| TaskError::Failed(format!("sync skin: {e}")))?;
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = match skin {
+				Some(_) => "皮肤已同步".to_string(),
+				None => "该账户未设置皮肤".to_string(),
+			};
+			guard.finished = true;
+		}
+		Ok(skin)
+	}
+}
+
+/// 上传本地皮肤文件
+pub struct UploadSkinTask {
+	pub account: AccountId,
+	pub path: PathBuf,
+	pub model: SkinModel,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for UploadSkinTask {
+	const TYPE_NAME: &'static str = "upload_skin";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for UploadSkinTask {
+	type Output = ();
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("account_skin", self.account.to_string())]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		if let Some(p) = &self.progress {
+			p.write().await.message = format!("上传 {}", self.path.display());
+		}
+		let png = tokio::fs::read(&self.path)
+			.await
+			.map_err(|e| TaskError::Failed(format!("read {}: {e}", self.path.display())))?;
+		AppState::get()
+			.accounts
+			.upload_skin(self.account, png, self.model)
+			.await
+			.map_err(|e| TaskError::Failed(format!("upload skin: {e}")))?;
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = "皮肤已上传".to_string();
+			guard.finished = true;
+		}
+		Ok(())
+	}
+}
+
+/// 切换正版披风，`cape` 为 None 时隐藏披风
+pub struct SelectCapeTask {
+	pub account: AccountId,
+	pub cape: Option<String>,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for SelectCapeTask {
+	const TYPE_NAME: &'static str = "select_cape";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for SelectCapeTask {
+	type Output = ();
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("account_skin", self.account.to_string())]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		let appearance = AppState::get()
+			.accounts
+			.select_cape(self.account, self.cape.as_deref())
+			.await
+			.map_err(|e| TaskError::Failed(format!("select cape: {e}")))?;
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = match appearance.active_cape() {
+				Some(cape) => format!("已切换披风 {}", cape.alias.as_deref().unwrap_or(&cape.id)),
+				None => "已隐藏披风".to_string(),
+			};
+			guard.finished = true;
+		}
+		Ok(())
+	}
+}
+
+/// 更换离线账户的 UUID，并把游戏目录下各存档的玩家数据迁移到新 UUID
+pub struct SwitchOfflineUuidTask {
+	pub account: AccountId,
+	pub uuid: OfflineUuid,
+}
+
+impl TaskType for SwitchOfflineUuidTask {
+	const TYPE_NAME: &'static str = "switch_offline_uuid";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for SwitchOfflineUuidTask {
+	type Output = PlayerdataMigration;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource(
+			"refresh_account",
+			self.account.to_string(),
+		)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		let state = AppState::get();
+		let (account, uuid) = (self.account, self.uuid);
+		let game_dir = state.cluster_path();
+		let (old, new, report) = tokio::task::spawn_blocking(move || {
+			switch_offline_uuid(&state.accounts, &game_dir, account, uuid)
+		})
+		.await
+		.map_err(|e| TaskError::Failed(e.to_string()))??;
+		for conflict in &report.conflicts {
+			tracing::warn!(
+				"Playerdata for {new} already exists, kept {}",
+				conflict.display()
+			);
+		}
+		tracing::info!(
+			"Offline UUID {old} -> {new}, migrated {} files",
+			report.moved.len()
+		);
+		Ok(report)
+	}
+}
+
+// 先迁移存档再保存新 UUID，迁移失败时账户保持原样
+fn switch_offline_uuid(
+	accounts: &AccountManager,
+	game_dir: &Path,
+	id: AccountId,
+	uuid: OfflineUuid,
+) -> TaskResult<(Uuid, Uuid, PlayerdataMigration)> {
+	let Some(Account::Offline {
+		username,
+		uuid: old,
+		..
+	}) = accounts.get(id)
+	else {
+		return Err(TaskError::Failed(format!("{id} is not an offline account")));
+	};
+	let (new, _) = uuid.resolve(&username);
+	let report = migrate_playerdata(game_dir, old, new)
+		.map_err(|e| TaskError::Failed(format!("migrate playerdata: {e}")))?;
+	accounts.set_offline_uuid(id, uuid);
+	Ok((old, new, report))
+}
+
+/// 后台定期刷新临近过期的正版账户令牌
+pub fn spawn_background_refresh() {
+	tokio::spawn(async {
+		let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+		loop {
+			interval.tick().await;
+			let state = AppState::get();
+			let due: Vec<AccountId> = state
+				.accounts
+				.list()
+				.into_iter()
+				.filter(|e| e.account.needs_refresh())
+				.map(|e| e.id)
+				.collect();
+
+			for id in due {
+				let task = RefreshAccountTask {
+					account: id,
+					force: false,
+				};
+				match state.task_manager.submit_concurrent(task).await {
+					Ok(mut h) => match h.result().await {
+						Ok(SessionState::Revoked) => {
+							tracing::warn!("Account {} session revoked, re-login required", id)
+						}
+						Ok(_) => {}
+						Err(e) => tracing::warn!("Background refresh of {} failed: {}", id, e),
+					},
+					Err(e) => tracing::debug!("Skip background refresh of {}: {}", id, e),
+				}
+			}
+		}
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_switch_offline_uuid_keeps_uuid_on_failure() {
+		let dir = tempfile::tempdir().unwrap();
+		// saves 是文件时无法读取存档列表
+		std::fs::write(dir.path().join("saves"), b"").unwrap();
+		let accounts = AccountManager::in_memory();
+		let id = accounts.add_offline_with("Steve", OfflineUuid::Legacy);
+		let before = *accounts.get(id).unwrap().uuid();
+
+		assert!(switch_offline_uuid(&accounts, dir.path(), id, OfflineUuid::Vanilla).is_err());
+		assert_eq!(*accounts.get(id).unwrap().uuid(), before);
+
+		std::fs::remove_file(dir.path().join("saves")).unwrap();
+		let (old, new, _) =
+			switch_offline_uuid(&accounts, dir.path(), id, OfflineUuid::Vanilla).unwrap();
+		assert_eq!(old, before);
+		assert_eq!(*accounts.get(id).unwrap().uuid(), new);
+	}
+}
@@ -1,10 +1,12 @@
-use crate::minecraft::game::args::{Features, current_arch, current_os_key, rule_allows};
-use crate::minecraft::profile::{Library, VersionProfile, load_version_profile};
-use crate::infrastructure::network::download::{DownloadClient, DownloadRequest};
+use crate::infrastructure::network::download::{Checksum, DownloadClient, DownloadRequest};
+use crate::launcher::core::state::AppState;
 use crate::launcher::task::error::{TaskError, TaskResult};
-use crate::launcher::task::lock::LockKey;
 use crate::launcher::task::framework::{ConcurrentTask, TaskContext, TaskType};
+use crate::launcher::task::lock::LockKey;
 use crate::launcher::task::sub_task::{SubTask, SubTaskChain, SubTaskContext};
+use crate::minecraft::game::args::{Features, current_arch, current_os_key, rule_allows};
+use crate::minecraft::game::maven::{MavenCoord, download_artifact, repositories};
+use crate::minecraft::profile::{Artifact, Library, VersionProfile, load_version_profile};
 use anyhow::Context;
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -30,6 +32,7 @@ struct DownloadContext {
 	version_id: String,
 	progress: Option<ProgressRef>,
 	profile: OnceCell<VersionProfile>,
+	maven_repositories: Vec<String>,
 }
 
 impl DownloadContext {
@@ -44,6 +47,7 @@ impl DownloadContext {
 			version_id,
 			progress,
 			profile: OnceCell::new(),
+			maven_repositories: AppState::get().config.get().maven_repositories,
 		})
 	}
 
@@ -193,11 +197,12 @@ impl SubTask for ClientJarTask {
 			return Ok(());
 		};
 
+		let jar_id = profile.jar_id(&s.version_id);
 		let dest = s
 			.game_dir
 			.join("versions")
-			.join(&s.version_id)
-			.join(format!("{}.jar", s.version_id));
+			.join(jar_id)
+			.join(format!("{jar_id}.jar"));
 
 		if dest.exists() {
 			return Ok(());
@@ -226,27 +231,61 @@ impl SubTask for CoreLibTask {
 			.profile()
 			.ok_or_else(|| TaskError::Failed("profile missing".into()))?;
 
-		let features = Features::default();
-		let os_key = current_os_key();
-		let arch = current_arch();
-
-		let requests: Vec<_> = profile
-			.libraries
-			.iter()
-			.filter(|lib| rule_allows(lib.rules.as_ref(), os_key, arch, &features))
-			.filter_map(|lib| library_request(&s.game_dir, lib, os_key))
-			.filter(|req| !req.dest.exists())
-			.collect();
+		download_libraries(
+			&s.client,
+			&s.game_dir,
+			&profile.libraries,
+			&s.maven_repositories,
+			&ctx.cancelled,
+		)
+		.await
+	}
+}
 
-		for req in requests {
-			check_cancel(&ctx.cancelled)?;
-			s.client
-				.download(req, |_| {}, Some(ctx.cancelled.clone()))
+/// 下载适用于当前平台且本地缺失的库
+pub async fn download_libraries(
+	client: &DownloadClient,
+	game_dir: &Path,
+	libraries: &[Library],
+	fallbacks: &[String],
+	cancel: &watch::Receiver<bool>,
+) -> TaskResult<()> {
+	let features = Features::default();
+	let os_key = current_os_key();
+	let arch = current_arch();
+
+	let sources: Vec<_> = libraries
+		.iter()
+		.filter(|lib| rule_allows(lib.rules.as_ref(), os_key, arch, &features))
+		.filter_map(|lib| library_source(game_dir, lib, os_key, arch, fallbacks))
+		.filter(|source| !source.dest().exists())
+		.collect();
+
+	for source in sources {
+		check_cancel(cancel)?;
+		match source {
+			LibrarySource::Direct(req) => client
+				.download(req, |_| {}, Some(cancel.clone()))
 				.await
-				.map_err(|e| TaskError::Failed(e.to_string()))?;
+				.map_err(|e| TaskError::Failed(e.to_string()))?,
+			LibrarySource::Maven {
+				coord,
+				repos,
+				sha1,
+				dest,
+			} => download_artifact(
+				client,
+				&coord,
+				&repos,
+				sha1.as_deref(),
+				&dest,
+				Some(cancel.clone()),
+			)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?,
 		}
-		Ok(())
 	}
+	Ok(())
 }
 
 struct AssetsTask(Arc<DownloadContext>);
@@ -330,35 +369,90 @@ fn check_cancel(cancel: &watch::Receiver<bool>) -> TaskResult<()> {
 	}
 }
 
-fn library_request(game_dir: &Path, lib: &Library, os_key: &str) -> Option<DownloadRequest> {
-	let downloads = lib.downloads.as_ref()?;
-
-	if let Some(natives) = &lib.natives {
-		if natives.contains_key(os_key) {
-			if let Some(classifiers) = &downloads.classifiers {
-				let key = format!("natives-{os_key}");
-				if let Some(artifact) = classifiers.get(&key) {
-					if let (Some(path), Some(url)) = (&artifact.path, &artifact.url) {
-						let dest = game_dir
-							.join("libraries")
-							.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
-						return Some(DownloadRequest::new(url.clone(), dest));
-					}
-				}
-			}
+enum LibrarySource {
+	Direct(DownloadRequest),
+	/// 只有坐标和仓库地址的库（Fabric、Forge 等），按仓库顺序尝试
+	Maven {
+		coord: MavenCoord,
+		repos: Vec<String>,
+		sha1: Option<String>,
+		dest: PathBuf,
+	},
+}
+
+impl LibrarySource {
+	fn dest(&self) -> &Path {
+		match self {
+			LibrarySource::Direct(req) => &req.dest,
+			LibrarySource::Maven { dest, .. } => dest,
 		}
 	}
+}
 
-	if let Some(artifact) = &downloads.artifact {
-		if let (Some(path), Some(url)) = (&artifact.path, &artifact.url) {
-			let dest = game_dir
-				.join("libraries")
-				.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
-			return Some(DownloadRequest::new(url.clone(), dest));
+fn library_source(
+	game_dir: &Path,
+	lib: &Library,
+	os_key: &str,
+	arch: &str,
+	fallbacks: &[String],
+) -> Option<LibrarySource> {
+	let libraries_dir = game_dir.join("libraries");
+	let local = |path: &str| libraries_dir.join(path.replace('/', std::path::MAIN_SEPARATOR_STR));
+	let direct = |artifact: &Artifact| {
+		let (path, url) = (artifact.path.as_ref()?, artifact.url.as_ref()?);
+		// Forge 安装器生成的库 url 为空，无法下载
+		if url.is_empty() {
+			return None;
 		}
+		let mut req = DownloadRequest::new(url.clone(), local(path));
+		if let Some(sha1) = &artifact.sha1 {
+			req = req.with_checksum(Checksum::Sha1(sha1.clone()));
+		}
+		Some(LibrarySource::Direct(req))
+	};
+	let downloads = lib.downloads.as_ref();
+
+	let mut classifier = None;
+	if let Some(natives) = &lib.natives
+		&& let Some(native_cls) = natives.get(os_key)
+	{
+		let key = format!("natives-{os_key}");
+		if let Some(artifact) = downloads
+			.and_then(|d| d.classifiers.as_ref())
+			.and_then(|c| c.get(&key))
+		{
+			return direct(artifact);
+		}
+		classifier = Some(native_cls.replace("${arch}", arch));
+	}
+
+	let artifact = downloads.and_then(|d| d.artifact.as_ref());
+	if classifier.is_none()
+		&& let Some(artifact) = artifact
+		&& artifact.url.is_some()
+	{
+		return direct(artifact);
+	}
+	if downloads.is_some() && lib.url.is_none() && classifier.is_none() {
+		return None;
 	}
 
-	None
+	let coord = MavenCoord::parse(&lib.name)
+		.ok()?
+		.with_classifier(classifier.as_deref());
+	let dest = match artifact.and_then(|a| a.path.as_deref()) {
+		Some(path) if classifier.is_none() => local(path),
+		_ => coord.local_path(&libraries_dir),
+	};
+	Some(LibrarySource::Maven {
+		repos: repositories(lib.url.as_deref(), fallbacks),
+		sha1: lib
+			.sha1
+			.clone()
+			.or_else(|| artifact.and_then(|a| a.sha1.clone())),
+		coord,
+		dest,
+	})
 }
 
 async fn resolve_version_url(version_id: &str) -> TaskResult<String> {
@@ -402,3 +496,78 @@ struct VersionRef {
 	id: String,
 	url: String,
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn library(json: &str) -> Library {
+		serde_json::from_str(json).unwrap()
+	}
+
+	#[test]
+	fn test_library_source_repository_order() {
+		let game_dir = Path::new("/game");
+		let fallbacks = [
+			"https://maven.fabricmc.net".to_string(),
+			"https://repo1.maven.org/maven2/".to_string(),
+		];
+		let lib = library(
+			r#"{"name":"net.fabricmc:fabric-loader:0.15.0","url":"https://maven.fabricmc.net/"}"#,
+		);
+
+		let Some(LibrarySource::Maven {
+			coord,
+			repos,
+			sha1,
+			dest,
+		}) = library_source(game_dir, &lib, "linux", "64", &fallbacks)
+		else {
+			panic!("expected a maven source");
+		};
+		// 库自带的仓库在前，与之相同的备用仓库不重复尝试
+		assert_eq!(
+			repos,
+			[
+				"https://maven.fabricmc.net",
+				"https://repo1.maven.org/maven2"
+			]
+		);
+		assert_eq!(coord.file_name(), "fabric-loader-0.15.0.jar");
+		assert!(sha1.is_none());
+		assert_eq!(dest, coord.local_path(&game_dir.join("libraries")));
+
+		// 没有 url 的库从 Mojang 仓库开始
+		let lib = library(r#"{"name":"org.ow2.asm:asm:9.6","sha1":"abc"}"#);
+		let Some(LibrarySource::Maven { repos, sha1, .. }) =
+			library_source(game_dir, &lib, "linux", "64", &fallbacks)
+		else {
+			panic!("expected a maven source");
+		};
+		assert_eq!(repos[0], "https://libraries.minecraft.net");
+		assert_eq!(repos.len(), 3);
+		assert_eq!(sha1.as_deref(), Some("abc"));
+	}
+
+	#[test]
+	fn test_library_source_direct() {
+		let lib = library(
+			r#"{"name":"com.mojang:brigadier:1.1.8","downloads":{"artifact":{
+				"path":"com/mojang/brigadier/1.1.8/brigadier-1.1.8.jar","sha1":"5244",
+				"url":"https://libraries.minecraft.net/com/mojang/brigadier/1.1.8/brigadier-1.1.8.jar"}}}"#,
+		);
+		let Some(LibrarySource::Direct(req)) =
+			library_source(Path::new("/game"), &lib, "linux", "64", &[])
+		else {
+			panic!("expected a direct download");
+		};
+		assert!(matches!(req.checksum, Some(Checksum::Sha1(ref s)) if s == "5244"));
+
+		// Forge 安装器生成的库没有下载地址
+		let lib = library(
+			r#"{"name":"net.minecraftforge:forge:1.20.1-47.2.0:client","downloads":{"artifact":{
+				"path":"net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-client.jar","url":""}}}"#,
+		);
+		assert!(library_source(Path::new("/game"), &lib, "linux", "64", &[]).is_none());
+	}
+}
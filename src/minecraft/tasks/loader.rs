@@ -0,0 +1,1231 @@
+use crate::infrastructure::network::download::{DownloadClient, DownloadRequest};
+use crate::launcher::core::paths;
+use crate::launcher::core::state::AppState;
+use crate::launcher::task::error::{TaskError, TaskResult};
+use crate::launcher::task::framework::{ConcurrentTask, TaskContext, TaskType};
+use crate::launcher::task::lock::LockKey;
+use crate::minecraft::game::instance::{GameInstance, InstanceMeta};
+use crate::minecraft::game::maven::{MavenCoord, download_artifact};
+use crate::minecraft::loader::detect::detect_instance;
+use crate::minecraft::loader::forge::{
+	self, ForgeInstaller, Processor, file_sha1, main_class, resolve_value,
+};
+use crate::minecraft::loader::meta::{MetaApi, pick_loader};
+use crate::minecraft::loader::neoforge;
+use crate::minecraft::loader::optifine::{self, OptiFineBuild};
+use crate::minecraft::loader::{LoaderInfo, LoaderKind};
+use crate::minecraft::profile::{
+	LibraryChange, VersionProfile, diff_libraries, load_version_profile, save_version_profile,
+};
+use crate::minecraft::tasks::download::{DownloadGameTask, ProgressRef, download_libraries};
+use crate::minecraft::tasks::runtime::resolve_java;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::fs;
+use tokio::process::Command;
+
+/// 安装 Fabric，生成 inheritsFrom 原版的子版本并下载全部依赖
+pub struct InstallFabricTask {
+	pub cluster_path: PathBuf,
+	pub game_version: String,
+	/// 不指定时使用最新的稳定版
+	pub loader_version: Option<String>,
+	/// 新版本的名称，默认使用 Fabric 给出的 `fabric-loader-<loader>-<game>`
+	pub name: Option<String>,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for InstallFabricTask {
+	const TYPE_NAME: &'static str = "install_fabric";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallFabricTask {
+	type Output = GameInstance;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("install_loader", &self.game_version)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let api = MetaApi::fabric(&AppState::get().config.get().fabric_meta);
+		let install = MetaInstall {
+			cluster_path: &self.cluster_path,
+			game_version: &self.game_version,
+			loader_version: self.loader_version.as_deref(),
+			name: self.name.as_deref(),
+			progress: &self.progress,
+		};
+		install.run(&api, ctx).await
+	}
+}
+
+/// 安装 Quilt，流程与 Fabric 相同
+pub struct InstallQuiltTask {
+	pub cluster_path: PathBuf,
+	pub game_version: String,
+	/// 不指定时使用最新的正式版
+	pub loader_version: Option<String>,
+	/// 新版本的名称，默认使用 Quilt 给出的 `quilt-loader-<loader>-<game>`
+	pub name: Option<String>,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for InstallQuiltTask {
+	const TYPE_NAME: &'static str = "install_quilt";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallQuiltTask {
+	type Output = GameInstance;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("install_loader", &self.game_version)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let api = MetaApi::quilt(&AppState::get().config.get().quilt_meta);
+		let install = MetaInstall {
+			cluster_path: &self.cluster_path,
+			game_version: &self.game_version,
+			loader_version: self.loader_version.as_deref(),
+			name: self.name.as_deref(),
+			progress: &self.progress,
+		};
+		install.run(&api, ctx).await
+	}
+}
+
+// 通过 meta API 安装的加载器共用的流程
+struct MetaInstall<'a> {
+	cluster_path: &'a Path,
+	game_version: &'a str,
+	loader_version: Option<&'a str>,
+	name: Option<&'a str>,
+	progress: &'a Option<ProgressRef>,
+}
+
+impl MetaInstall<'_> {
+	async fn run(&self, api: &MetaApi, ctx: &TaskContext) -> TaskResult<GameInstance> {
+		let kind = api.kind;
+		set_progress(
+			self.progress,
+			&format!("获取 {kind} 版本列表 {}", self.game_version),
+		)
+		.await;
+		let versions = api
+			.loader_versions(self.game_version)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let loader = pick_loader(&versions, self.loader_version)
+			.ok_or_else(|| {
+				TaskError::Failed(match self.loader_version {
+					Some(v) => format!(
+						"{kind} loader {v} does not support Minecraft {}",
+						self.game_version
+					),
+					None => format!("{kind} does not support Minecraft {}", self.game_version),
+				})
+			})?
+			.version
+			.clone();
+
+		download_version(self.cluster_path, self.game_version, self.progress, ctx).await?;
+
+		let mut profile = api
+			.loader_profile(self.game_version, &loader)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let id = self.name.map_or_else(|| profile.id.clone(), String::from);
+		write_child_profile(self.cluster_path, &id, self.game_version, &mut profile)?;
+		record_loader(
+			self.cluster_path,
+			&id,
+			self.game_version,
+			LoaderInfo {
+				kind,
+				version: loader.clone(),
+			},
+		)?;
+
+		set_progress(self.progress, &format!("下载 {kind} {loader} 依赖库")).await;
+		download_version(self.cluster_path, &id, self.progress, ctx).await?;
+
+		let instance = registered_instance(self.cluster_path, &id);
+		if let Some(p) = self.progress {
+			let mut guard = p.write().await;
+			guard.message = format!("{id} 安装完成");
+			guard.finished = true;
+		}
+		Ok(instance)
+	}
+}
+
+/// 安装 Forge：1.13 及以后运行安装器中的处理器生成补丁后的客户端，
+/// 1.5–1.12 的旧版安装器解压 universal jar 并使用 LaunchWrapper 启动
+pub struct InstallForgeTask {
+	pub cluster_path: PathBuf,
+	pub game_version: String,
+	/// 如 `47.2.0`；1.7.10 等旧版本带分支后缀，如 `10.13.4.1614-1.7.10`。
+	/// 不指定时使用推荐构建，没有推荐构建时使用最新构建
+	pub forge_version: Option<String>,
+	/// 新版本的名称，默认使用安装器给出的 `<game>-forge-<forge>`
+	pub name: Option<String>,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for InstallForgeTask {
+	const TYPE_NAME: &'static str = "install_forge";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallForgeTask {
+	type Output = GameInstance;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("install_loader", &self.game_version)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let repo = AppState::get().config.get().forge_maven;
+		let version = match &self.forge_version {
+			Some(v) => v.clone(),
+			None => {
+				set_progress(
+					&self.progress,
+					&format!("获取 Forge 版本列表 {}", self.game_version),
+				)
+				.await;
+				forge::promotions(&repo)
+					.await
+					.map_err(|e| TaskError::Failed(e.to_string()))?
+					.pick(&self.game_version)
+					.cloned()
+					.ok_or_else(|| {
+						TaskError::Failed(format!(
+							"Forge does not support Minecraft {}",
+							self.game_version
+						))
+					})?
+			}
+		};
+		let coord = MavenCoord::parse(&format!(
+			"net.minecraftforge:forge:{}-{}:installer",
+			self.game_version, version
+		))
+		.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let install = InstallerInstall {
+			cluster_path: &self.cluster_path,
+			game_version: &self.game_version,
+			name: self.name.as_deref(),
+			progress: &self.progress,
+			loader: LoaderInfo {
+				kind: LoaderKind::Forge,
+				version,
+			},
+		};
+		install.run(&coord, &repo, ctx).await
+	}
+}
+
+/// 安装 NeoForge；`name` 为已有 NeoForge 实例时原地升级，实例设置保持不变
+pub struct InstallNeoForgeTask {
+	pub cluster_path: PathBuf,
+	pub game_version: String,
+	/// 不指定时使用最新的非 beta 构建
+	pub neoforge_version: Option<String>,
+	/// 新版本的名称，默认使用安装器给出的 `neoforge-<version>`
+	pub name: Option<String>,
+	pub progress: Option<ProgressRef>,
+}
+
+impl InstallNeoForgeTask {
+	/// 把已有的 NeoForge 实例升级到指定版本
+	pub fn upgrade(
+		instance: &GameInstance,
+		neoforge_version: Option<String>,
+		progress: Option<ProgressRef>,
+	) -> TaskResult<Self> {
+		let meta = InstanceMeta::load(&instance.cluster_path, &instance.version);
+		let game_version = match (&meta.loader, meta.game_version) {
+			(Some(loader), Some(game)) if loader.kind == LoaderKind::NeoForge => game,
+			_ => {
+				return Err(TaskError::Failed(format!(
+					"{} is not a NeoForge instance installed by Hako",
+					instance.version
+				)));
+			}
+		};
+		Ok(Self {
+			cluster_path: instance.cluster_path.clone(),
+			game_version,
+			neoforge_version,
+			name: Some(instance.version.clone()),
+			progress,
+		})
+	}
+}
+
+impl TaskType for InstallNeoForgeTask {
+	const TYPE_NAME: &'static str = "install_neoforge";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallNeoForgeTask {
+	type Output = GameInstance;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("install_loader", &self.game_version)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let repo = AppState::get().config.get().neoforge_maven;
+		set_progress(
+			&self.progress,
+			&format!("获取 NeoForge 版本列表 {}", self.game_version),
+		)
+		.await;
+		let versions = neoforge::neoforge_versions(&repo, &self.game_version)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let version = neoforge::pick_version(&versions, self.neoforge_version.as_deref())
+			.ok_or_else(|| {
+				TaskError::Failed(match &self.neoforge_version {
+					Some(v) => format!(
+						"NeoForge {v} is not available for Minecraft {}",
+						self.game_version
+					),
+					None => format!("NeoForge does not support Minecraft {}", self.game_version),
+				})
+			})?
+			.clone();
+
+		let coord = neoforge::installer_coord(&self.game_version, &version)
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let install = InstallerInstall {
+			cluster_path: &self.cluster_path,
+			game_version: &self.game_version,
+			name: self.name.as_deref(),
+			progress: &self.progress,
+			loader: LoaderInfo {
+				kind: LoaderKind::NeoForge,
+				version,
+			},
+		};
+		install.run(&coord, &repo, ctx).await
+	}
+}
+
+/// OptiFine 的安装方式
+pub enum OptiFineTarget {
+	/// 生成独立的 LaunchWrapper 版本，名称默认为 `<game>-OptiFine_<build>`
+	Standalone { name: Option<String> },
+	/// 放入已安装加载器的实例的 mods 目录
+	Mods(GameInstance),
+}
+
+pub struct InstallOptiFineTask {
+	pub cluster_path: PathBuf,
+	pub game_version: String,
+	/// 如 `HD_U_I6`，不指定时使用最新的正式版
+	pub build: Option<String>,
+	pub target: OptiFineTarget,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for InstallOptiFineTask {
+	const TYPE_NAME: &'static str = "install_optifine";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallOptiFineTask {
+	type Output = GameInstance;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("install_loader", &self.game_version)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let source = AppState::get().config.get().optifine_source;
+		set_progress(
+			&self.progress,
+			&format!("获取 OptiFine 版本列表 {}", self.game_version),
+		)
+		.await;
+		let builds = optifine::builds(&source, &self.game_version)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let build = optifine::pick_build(&builds, self.build.as_deref())
+			.ok_or_else(|| {
+				TaskError::Failed(match &self.build {
+					Some(b) => format!(
+						"OptiFine {b} is not available for Minecraft {}",
+						self.game_version
+					),
+					None => format!("OptiFine does not support Minecraft {}", self.game_version),
+				})
+			})?
+			.clone();
+
+		// 放进 mods 前先检查兼容性，避免白白下载
+		if let OptiFineTarget::Mods(instance) = &self.target {
+			check_optifine_addon(instance, &build)?;
+		}
+
+		set_progress(&self.progress, &format!("下载 OptiFine {}", build.name())).await;
+		let installer = paths::cache_dir()
+			.map_err(|e| TaskError::Failed(e.to_string()))?
+			.join(&build.filename);
+		DownloadClient::new()
+			.map_err(|e| TaskError::Failed(e.to_string()))?
+			.download(
+				DownloadRequest::new(build.download_url(&source), &installer),
+				|_| {},
+				Some(ctx.cancelled_receiver()),
+			)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+
+		let result = match &self.target {
+			OptiFineTarget::Mods(instance) => {
+				let mods = instance.cluster_path.join("mods");
+				fs::create_dir_all(&mods)
+					.await
+					.map_err(|e| TaskError::Failed(e.to_string()))?;
+				fs::copy(&installer, mods.join(&build.filename))
+					.await
+					.map_err(|e| TaskError::Failed(e.to_string()))?;
+				Ok(instance.clone())
+			}
+			OptiFineTarget::Standalone { name } => {
+				let id = name
+					.clone()
+					.unwrap_or_else(|| format!("{}-OptiFine_{}", self.game_version, build.name()));
+				self.install_standalone(&build, &installer, &id, ctx).await
+			}
+		};
+		let _ = fs::remove_file(&installer).await;
+		let instance = result?;
+
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = format!("OptiFine {} 安装完成", build.name());
+			guard.finished = true;
+		}
+		Ok(instance)
+	}
+}
+
+impl InstallOptiFineTask {
+	async fn install_standalone(
+		&self,
+		build: &OptiFineBuild,
+		installer: &Path,
+		id: &str,
+		ctx: &TaskContext,
+	) -> TaskResult<GameInstance> {
+		let failed = |e: anyhow::Error| TaskError::Failed(e.to_string());
+		download_version(&self.cluster_path, &self.game_version, &self.progress, ctx).await?;
+		let libraries_dir = self.cluster_path.join("libraries");
+
+		let library = MavenCoord::parse(&build.library_name())
+			.map_err(failed)?
+			.local_path(&libraries_dir);
+		if let Some(parent) = library.parent() {
+			fs::create_dir_all(parent)
+				.await
+				.map_err(|e| TaskError::Failed(e.to_string()))?;
+		}
+		if optifine::has_patcher(installer).map_err(failed)? {
+			let parent =
+				load_version_profile(&self.cluster_path, &self.game_version).map_err(failed)?;
+			let java = resolve_java(&parent, None).await?;
+			let minecraft_jar = self
+				.cluster_path
+				.join("versions")
+				.join(&self.game_version)
+				.join(format!("{}.jar", self.game_version));
+			set_progress(&self.progress, &format!("生成 OptiFine {}", build.name())).await;
+			let output = Command::new(java)
+				.arg("-cp")
+				.arg(installer)
+				.arg("optifine.Patcher")
+				.arg(&minecraft_jar)
+				.arg(installer)
+				.arg(&library)
+				.stdin(Stdio::null())
+				.kill_on_drop(true)
+				.output()
+				.await
+				.map_err(|e| TaskError::Failed(format!("run OptiFine patcher: {e}")))?;
+			if !output.status.success() || !library.exists() {
+				return Err(TaskError::Failed(format!(
+					"OptiFine patcher exited with {:?}: {}",
+					output.status.code(),
+					String::from_utf8_lossy(&output.stderr).trim()
+				)));
+			}
+		} else {
+			fs::copy(installer, &library)
+				.await
+				.map_err(|e| TaskError::Failed(e.to_string()))?;
+		}
+
+		let launchwrapper = match optifine::bundled_launchwrapper(installer).map_err(failed)? {
+			Some((version, jar)) => {
+				let name = format!("optifine:launchwrapper-of:{version}");
+				let path = MavenCoord::parse(&name)
+					.map_err(failed)?
+					.local_path(&libraries_dir);
+				if let Some(parent) = path.parent() {
+					fs::create_dir_all(parent)
+						.await
+						.map_err(|e| TaskError::Failed(e.to_string()))?;
+				}
+				fs::write(&path, jar)
+					.await
+					.map_err(|e| TaskError::Failed(e.to_string()))?;
+				Some(name)
+			}
+			None => None,
+		};
+
+		let parent =
+			load_version_profile(&self.cluster_path, &self.game_version).map_err(failed)?;
+		let profile = optifine::standalone_profile(build, id, &parent, launchwrapper.as_deref());
+		let fallbacks = AppState::get().config.get().maven_repositories;
+		download_libraries(
+			&DownloadClient::new().map_err(|e| TaskError::Failed(e.to_string()))?,
+			&self.cluster_path,
+			&profile.libraries,
+			&fallbacks,
+			&ctx.cancelled_receiver(),
+		)
+		.await?;
+		save_version_profile(&self.cluster_path, id, &profile).map_err(failed)?;
+		record_loader(
+			&self.cluster_path,
+			id,
+			&self.game_version,
+			LoaderInfo {
+				kind: LoaderKind::OptiFine,
+				version: build.name(),
+			},
+		)?;
+		Ok(registered_instance(&self.cluster_path, id))
+	}
+}
+
+// 作为附加模组时要求实例已有 Forge 或 Fabric 系加载器，且游戏版本与构建一致
+fn check_optifine_addon(instance: &GameInstance, build: &OptiFineBuild) -> TaskResult<()> {
+	let id = &instance.version;
+	let detected = detect_instance(&instance.cluster_path, id)
+		.map_err(|e| TaskError::Failed(format!("read {id}: {e}")))?;
+	let Some(loader) = detected.loader else {
+		return Err(TaskError::Failed(format!(
+			"{id} has no mod loader, install OptiFine as a standalone version instead"
+		)));
+	};
+	if detected.game_version != build.game_version {
+		return Err(TaskError::Failed(format!(
+			"OptiFine {} is for Minecraft {}, but {id} is {}",
+			build.name(),
+			build.game_version,
+			detected.game_version
+		)));
+	}
+	match loader.kind {
+		LoaderKind::Forge if !build.supports_forge() => Err(TaskError::Failed(format!(
+			"OptiFine {} does not work with Forge",
+			build.name()
+		))),
+		LoaderKind::Forge => Ok(()),
+		LoaderKind::NeoForge => Err(TaskError::Failed(format!(
+			"OptiFine does not support NeoForge, {id} cannot load it as a mod"
+		))),
+		LoaderKind::Fabric | LoaderKind::Quilt => {
+			tracing::warn!(
+				"OptiFine on {} needs OptiFabric in the mods folder",
+				loader.kind
+			);
+			Ok(())
+		}
+		LoaderKind::OptiFine => Err(TaskError::Failed(format!(
+			"{id} already is an OptiFine version"
+		))),
+	}
+}
+
+/// 切换已有实例的加载器：重新安装为指定的加载器与版本，或移除加载器回到原版。
+/// 旧的版本 JSON 与实例记录保存在 `versions/<id>/Hako/` 下作为回滚点
+pub struct SwitchLoaderTask {
+	pub instance: GameInstance,
+	/// None 表示移除加载器
+	pub loader: Option<LoaderKind>,
+	/// 不指定时使用最新的稳定版
+	pub loader_version: Option<String>,
+	pub progress: Option<ProgressRef>,
+}
+
+/// 切换加载器的结果
+#[derive(Debug, Clone)]
+pub struct LoaderSwitch {
+	pub instance: GameInstance,
+	pub previous: Option<LoaderInfo>,
+	pub current: Option<LoaderInfo>,
+	/// 切换前的版本 JSON，可用 [`rollback_loader`] 连同实例记录一起恢复
+	pub rollback: PathBuf,
+	pub libraries: Vec<LibraryChange>,
+}
+
+impl TaskType for SwitchLoaderTask {
+	const TYPE_NAME: &'static str = "switch_loader";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for SwitchLoaderTask {
+	type Output = LoaderSwitch;
+
+	// 安装任务自己会持有 install_loader 锁，这里只锁实例本身
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("switch_loader", &self.instance.version)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let cluster_path = self.instance.cluster_path.clone();
+		let id = self.instance.version.clone();
+		let before = detect_instance(&cluster_path, &id)
+			.map_err(|e| TaskError::Failed(format!("read {id}: {e}")))?;
+		let game_version = before.game_version.clone();
+		if game_version == id {
+			return Err(TaskError::Failed(format!(
+				"{id} is the vanilla version itself, install a loader as a new instance instead"
+			)));
+		}
+		if self.loader.is_none() && before.loader.is_none() {
+			return Err(TaskError::Failed(format!("{id} has no mod loader")));
+		}
+
+		let rollback =
+			with_rollback(&cluster_path, &id, self.reinstall(&game_version, ctx)).await?;
+
+		let after = detect_instance(&cluster_path, &id)
+			.map_err(|e| TaskError::Failed(format!("read {id}: {e}")))?;
+		let libraries = diff_libraries(&before.profile.libraries, &after.profile.libraries);
+		for change in &libraries {
+			tracing::info!(
+				"{id}: {} -> {}",
+				change.old.as_deref().unwrap_or("(none)"),
+				change.new.as_deref().unwrap_or("(none)")
+			);
+		}
+		let instance = registered_instance(&cluster_path, &id);
+		if let Some(p) = &self.progress {
+			let mut guard = p.write().await;
+			guard.message = match &after.loader {
+				Some(l) => format!("{id} 已切换到 {} {}", l.kind, l.version),
+				None => format!("{id} 已恢复为原版 {game_version}"),
+			};
+			guard.finished = true;
+		}
+		Ok(LoaderSwitch {
+			instance,
+			previous: before.loader,
+			current: after.loader,
+			rollback,
+			libraries,
+		})
+	}
+}
+
+impl SwitchLoaderTask {
+	// 以实例名作为安装名称，原地覆盖版本 JSON，实例设置与游戏文件保持不变
+	async fn reinstall(&self, game_version: &str, ctx: &TaskContext) -> TaskResult<()> {
+		let cluster_path = self.instance.cluster_path.clone();
+		let id = self.instance.version.clone();
+		let game_version = game_version.to_string();
+		let progress = self.progress.clone();
+		let loader_version = self.loader_version.clone();
+		let installed = match self.loader {
+			None => {
+				download_version(&cluster_path, &game_version, &progress, ctx).await?;
+				write_child_profile(
+					&cluster_path,
+					&id,
+					&game_version,
+					&mut VersionProfile::default(),
+				)?;
+				let mut meta = InstanceMeta::load(&cluster_path, &id);
+				meta.game_version = Some(game_version);
+				meta.loader = None;
+				return meta
+					.save(&cluster_path, &id)
+					.map_err(|e| TaskError::Failed(format!("save instance meta: {e}")));
+			}
+			Some(LoaderKind::Fabric) => {
+				install_nested(
+					InstallFabricTask {
+						cluster_path,
+						game_version,
+						loader_version,
+						name: Some(id),
+						progress,
+					},
+					ctx,
+				)
+				.await
+			}
+			Some(LoaderKind::Quilt) => {
+				install_nested(
+					InstallQuiltTask {
+						cluster_path,
+						game_version,
+						loader_version,
+						name: Some(id),
+						progress,
+					},
+					ctx,
+				)
+				.await
+			}
+			Some(LoaderKind::Forge) => {
+				install_nested(
+					InstallForgeTask {
+						cluster_path,
+						game_version,
+						forge_version: loader_version,
+						name: Some(id),
+						progress,
+					},
+					ctx,
+				)
+				.await
+			}
+			Some(LoaderKind::NeoForge) => {
+				install_nested(
+					InstallNeoForgeTask {
+						cluster_path,
+						game_version,
+						neoforge_version: loader_version,
+						name: Some(id),
+						progress,
+					},
+					ctx,
+				)
+				.await
+			}
+			Some(LoaderKind::OptiFine) => {
+				install_nested(
+					InstallOptiFineTask {
+						cluster_path,
+						game_version,
+						build: loader_version,
+						target: OptiFineTarget::Standalone { name: Some(id) },
+						progress,
+					},
+					ctx,
+				)
+				.await
+			}
+		};
+		installed.map(|_| ())
+	}
+}
+
+// 先备份版本 JSON 与实例记录；部分安装流程会先写入其中之一，失败时两者一并还原
+async fn with_rollback(
+	cluster_path: &Path,
+	id: &str,
+	reinstall: impl Future<Output = TaskResult<()>>,
+) -> TaskResult<PathBuf> {
+	back_up_instance(cluster_path, id)?;
+	if let Err(e) = reinstall.await {
+		if let Err(restore) = restore_instance(cluster_path, id) {
+			tracing::error!("Restore {id} failed: {restore}");
+		}
+		return Err(e);
+	}
+	Ok(rollback_path(cluster_path, id))
+}
+
+fn back_up_instance(cluster_path: &Path, id: &str) -> TaskResult<()> {
+	let rollback = rollback_path(cluster_path, id);
+	if let Some(dir) = rollback.parent() {
+		std::fs::create_dir_all(dir).map_err(|e| TaskError::Failed(e.to_string()))?;
+	}
+	std::fs::copy(profile_path(cluster_path, id), &rollback)
+		.map_err(|e| TaskError::Failed(format!("back up {id}.json: {e}")))?;
+	let meta = InstanceMeta::path(cluster_path, id);
+	let meta_rollback = rollback_meta_path(cluster_path, id);
+	let result = if meta.exists() {
+		std::fs::copy(&meta, &meta_rollback).map(|_| ())
+	} else if meta_rollback.exists() {
+		std::fs::remove_file(&meta_rollback)
+	} else {
+		Ok(())
+	};
+	result.map_err(|e| TaskError::Failed(format!("back up instance meta of {id}: {e}")))
+}
+
+// 切换前没有实例记录时删除切换中写入的记录
+fn restore_instance(cluster_path: &Path, id: &str) -> TaskResult<()> {
+	let rollback = rollback_path(cluster_path, id);
+	if !rollback.exists() {
+		return Err(TaskError::Failed(format!("{id} has no rollback point")));
+	}
+	std::fs::copy(&rollback, profile_path(cluster_path, id))
+		.map_err(|e| TaskError::Failed(format!("restore {id}.json: {e}")))?;
+	let meta = InstanceMeta::path(cluster_path, id);
+	let meta_rollback = rollback_meta_path(cluster_path, id);
+	let result = if meta_rollback.exists() {
+		std::fs::copy(&meta_rollback, &meta).map(|_| ())
+	} else if meta.exists() {
+		std::fs::remove_file(&meta)
+	} else {
+		Ok(())
+	};
+	result.map_err(|e| TaskError::Failed(format!("restore instance meta of {id}: {e}")))
+}
+
+// 安装任务在切换任务内直接运行，共用取消信号，也不额外占用并发额度
+async fn install_nested<T: ConcurrentTask<Output = GameInstance>>(
+	task: T,
+	ctx: &TaskContext,
+) -> TaskResult<GameInstance> {
+	AppState::get().task_manager.run_nested(task, ctx).await
+}
+
+/// 恢复切换加载器之前的版本 JSON，返回恢复后识别到的加载器
+pub fn rollback_loader(instance: &GameInstance) -> TaskResult<Option<LoaderInfo>> {
+	let (cluster_path, id) = (&instance.cluster_path, &instance.version);
+	restore_instance(cluster_path, id)?;
+	let detected =
+		detect_instance(cluster_path, id).map_err(|e| TaskError::Failed(e.to_string()))?;
+	let mut meta = InstanceMeta::load(cluster_path, id);
+	meta.game_version = Some(detected.game_version);
+	meta.loader = detected.loader.clone();
+	meta.save(cluster_path, id)
+		.map_err(|e| TaskError::Failed(format!("save instance meta: {e}")))?;
+	Ok(detected.loader)
+}
+
+fn profile_path(cluster_path: &Path, id: &str) -> PathBuf {
+	cluster_path
+		.join("versions")
+		.join(id)
+		.join(format!("{id}.json"))
+}
+
+fn rollback_path(cluster_path: &Path, id: &str) -> PathBuf {
+	cluster_path
+		.join("versions")
+		.join(id)
+		.join("Hako")
+		.join("rollback.json")
+}
+
+fn rollback_meta_path(cluster_path: &Path, id: &str) -> PathBuf {
+	rollback_path(cluster_path, id).with_file_name("rollback-instance.yml")
+}
+
+// 基于 install_profile.json 的安装器共用的流程
+struct InstallerInstall<'a> {
+	cluster_path: &'a Path,
+	game_version: &'a str,
+	name: Option<&'a str>,
+	progress: &'a Option<ProgressRef>,
+	loader: LoaderInfo,
+}
+
+impl InstallerInstall<'_> {
+	async fn run(
+		&self,
+		installer: &MavenCoord,
+		repo: &str,
+		ctx: &TaskContext,
+	) -> TaskResult<GameInstance> {
+		let kind = self.loader.kind;
+		let version = &self.loader.version;
+		download_version(self.cluster_path, self.game_version, self.progress, ctx).await?;
+
+		set_progress(self.progress, &format!("下载 {kind} {version} 安装器")).await;
+		let client = DownloadClient::new().map_err(|e| TaskError::Failed(e.to_string()))?;
+		let cache = paths::cache_dir().map_err(|e| TaskError::Failed(e.to_string()))?;
+		let installer_path = cache.join(installer.file_name());
+		download_artifact(
+			&client,
+			installer,
+			&[repo.to_string()],
+			None,
+			&installer_path,
+			Some(ctx.cancelled_receiver()),
+		)
+		.await
+		.map_err(|e| TaskError::Failed(e.to_string()))?;
+
+		let libraries_dir = self.cluster_path.join("libraries");
+		let installer = {
+			let (path, libraries_dir) = (installer_path.clone(), libraries_dir.clone());
+			tokio::task::spawn_blocking(move || {
+				let installer = ForgeInstaller::open(&path)?;
+				installer.extract_maven(&libraries_dir)?;
+				Ok::<_, anyhow::Error>(installer)
+			})
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?
+			.map_err(|e| TaskError::Failed(format!("read {kind} installer: {e}")))?
+		};
+		if installer.profile.minecraft != self.game_version {
+			return Err(TaskError::Failed(format!(
+				"{kind} {version} is for Minecraft {}, not {}",
+				installer.profile.minecraft, self.game_version
+			)));
+		}
+
+		set_progress(self.progress, &format!("下载 {kind} {version} 依赖库")).await;
+		// 旧版 Forge 的库常只写着已失效的 files.minecraftforge.net，先回退到安装器所在的仓库
+		let mut fallbacks = vec![repo.to_string()];
+		fallbacks.extend(AppState::get().config.get().maven_repositories);
+		for libraries in [&installer.profile.libraries, &installer.version.libraries] {
+			download_libraries(
+				&client,
+				self.cluster_path,
+				libraries,
+				&fallbacks,
+				&ctx.cancelled_receiver(),
+			)
+			.await?;
+		}
+
+		// 旧版安装器与部分新版安装器没有客户端处理器，此时不需要 Java
+		if installer
+			.profile
+			.processors
+			.iter()
+			.any(Processor::runs_on_client)
+		{
+			let vanilla = load_version_profile(self.cluster_path, self.game_version)
+				.map_err(|e| TaskError::Failed(e.to_string()))?;
+			let java = resolve_java(&vanilla, None).await?;
+			let minecraft_jar = self
+				.cluster_path
+				.join("versions")
+				.join(self.game_version)
+				.join(format!("{}.jar", self.game_version));
+			let work_dir = installer_path.with_extension("work");
+			let data = installer
+				.data_map(self.cluster_path, &minecraft_jar, &work_dir)
+				.map_err(|e| TaskError::Failed(format!("prepare {kind} installer data: {e}")))?;
+			let result =
+				run_processors(&installer, &java, &data, &libraries_dir, self.progress, ctx).await;
+			let _ = fs::remove_dir_all(&work_dir).await;
+			result?;
+		}
+
+		// 处理器全部成功后才写入版本 JSON，原地升级失败时旧版本仍可启动
+		let mut profile = installer.version.clone();
+		let id = self.name.map_or_else(|| profile.id.clone(), String::from);
+		write_child_profile(self.cluster_path, &id, self.game_version, &mut profile)?;
+		record_loader(
+			self.cluster_path,
+			&id,
+			self.game_version,
+			self.loader.clone(),
+		)?;
+		let _ = fs::remove_file(&installer_path).await;
+
+		let instance = registered_instance(self.cluster_path, &id);
+		if let Some(p) = self.progress {
+			let mut guard = p.write().await;
+			guard.message = format!("{id} 安装完成");
+			guard.finished = true;
+		}
+		Ok(instance)
+	}
+}
+
+// 依次运行客户端处理器；输出均已存在且校验通过的处理器跳过
+async fn run_processors(
+	installer: &ForgeInstaller,
+	java: &Path,
+	data: &HashMap<String, String>,
+	libraries_dir: &Path,
+	progress: &Option<ProgressRef>,
+	ctx: &TaskContext,
+) -> TaskResult<()> {
+	let failed = |e: anyhow::Error| TaskError::Failed(e.to_string());
+	let processors: Vec<&Processor> = installer
+		.profile
+		.processors
+		.iter()
+		.filter(|p| p.runs_on_client())
+		.collect();
+	let total = processors.len() as u64;
+
+	for (i, processor) in processors.into_iter().enumerate() {
+		if ctx.is_cancelled() {
+			return Err(TaskError::Cancelled);
+		}
+		if let Some(p) = progress {
+			let mut guard = p.write().await;
+			guard.message = format!("运行安装处理器 {}/{total}", i + 1);
+			guard.downloaded = i as u64;
+			guard.total = Some(total);
+		}
+
+		let mut outputs = Vec::new();
+		for (file, sha1) in &processor.outputs {
+			outputs.push((
+				resolve_value(file, data, libraries_dir).map_err(failed)?,
+				resolve_value(sha1, data, libraries_dir).map_err(failed)?,
+			));
+		}
+		if !outputs.is_empty()
+			&& outputs
+				.iter()
+				.all(|(file, sha1)| output_matches(file, sha1))
+		{
+			continue;
+		}
+
+		let jar = MavenCoord::parse(&processor.jar)
+			.map_err(failed)?
+			.local_path(libraries_dir);
+		let main = main_class(&jar).map_err(failed)?;
+		let mut classpath = vec![jar];
+		for coord in &processor.classpath {
+			classpath.push(
+				MavenCoord::parse(coord)
+					.map_err(failed)?
+					.local_path(libraries_dir),
+			);
+		}
+		let classpath =
+			std::env::join_paths(classpath).map_err(|e| TaskError::Failed(e.to_string()))?;
+		let args = processor
+			.args
+			.iter()
+			.map(|a| resolve_value(a, data, libraries_dir))
+			.collect::<anyhow::Result<Vec<_>>>()
+			.map_err(failed)?;
+
+		tracing::debug!("Running processor {} {:?}", processor.jar, args);
+		let child = Command::new(java)
+			.arg("-cp")
+			.arg(classpath)
+			.arg(&main)
+			.args(&args)
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped())
+			.kill_on_drop(true)
+			.spawn()
+			.map_err(|e| TaskError::Failed(format!("run processor {}: {e}", processor.jar)))?;
+		let mut cancelled = ctx.cancelled_receiver();
+		let output = tokio::select! {
+			output = child.wait_with_output() => output
+				.map_err(|e| TaskError::Failed(format!("run processor {}: {e}", processor.jar)))?,
+			_ = cancelled.changed() => return Err(TaskError::Cancelled),
+		};
+		if !output.status.success() {
+			let stderr = String::from_utf8_lossy(&output.stderr);
+			let tail: Vec<&str> = stderr.lines().rev().take(5).collect();
+			return Err(TaskError::Failed(format!(
+				"Processor {} exited with {:?}: {}",
+				processor.jar,
+				output.status.code(),
+				tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+			)));
+		}
+
+		for (file, sha1) in &outputs {
+			if !output_matches(file, sha1) {
+				let _ = fs::remove_file(file).await;
+				return Err(TaskError::Failed(format!(
+					"Processor {} produced {file} with unexpected checksum",
+					processor.jar
+				)));
+			}
+		}
+	}
+	Ok(())
+}
+
+fn output_matches(file: &str, sha1: &str) -> bool {
+	file_sha1(Path::new(file)).is_ok_and(|actual| actual.eq_ignore_ascii_case(sha1))
+}
+
+async fn set_progress(progress: &Option<ProgressRef>, message: &str) {
+	if let Some(p) = progress {
+		p.write().await.message = message.to_string();
+	}
+}
+
+// 借用原版下载任务补齐版本 JSON、客户端、库与资源，在当前任务内运行以便一同取消
+async fn download_version(
+	cluster_path: &Path,
+	version: &str,
+	progress: &Option<ProgressRef>,
+	ctx: &TaskContext,
+) -> TaskResult<()> {
+	if ctx.is_cancelled() {
+		return Err(TaskError::Cancelled);
+	}
+	let task = DownloadGameTask {
+		cluster_path: cluster_path.to_path_buf(),
+		version: version.to_string(),
+		progress: progress.clone(),
+	};
+	AppState::get().task_manager.run_nested(task, ctx).await
+}
+
+// 子版本直接使用原版的客户端 jar，不再复制一份
+fn write_child_profile(
+	cluster_path: &Path,
+	id: &str,
+	game_version: &str,
+	profile: &mut VersionProfile,
+) -> TaskResult<()> {
+	profile.id = id.to_string();
+	profile.inherits_from = Some(game_version.to_string());
+	profile.jar = Some(game_version.to_string());
+	save_version_profile(cluster_path, id, profile).map_err(|e| TaskError::Failed(e.to_string()))
+}
+
+fn record_loader(
+	cluster_path: &Path,
+	id: &str,
+	game_version: &str,
+	loader: LoaderInfo,
+) -> TaskResult<()> {
+	let mut meta = InstanceMeta::load(cluster_path, id);
+	meta.game_version = Some(game_version.to_string());
+	meta.loader = Some(loader);
+	meta.save(cluster_path, id)
+		.map_err(|e| TaskError::Failed(format!("save instance meta: {e}")))
+}
+
+// 重新扫描实例列表，返回新安装的版本
+fn registered_instance(cluster_path: &Path, id: &str) -> GameInstance {
+	let state = AppState::get();
+	state.scan_instances();
+	let found = state
+		.instances
+		.read()
+		.unwrap()
+		.iter()
+		.find(|i| i.version == id && i.cluster_path == cluster_path)
+		.cloned();
+	found.unwrap_or_else(|| GameInstance::load(cluster_path, id))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn write(dir: &Path, version: &str, file: &str, content: &str) {
+		let dir = dir.join("versions").join(version);
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join(file), content).unwrap();
+	}
+
+	fn child(dir: &Path, id: &str, library: &str) -> GameInstance {
+		write(
+			dir,
+			id,
+			&format!("{id}.json"),
+			&format!(
+				r#"{{"id":"{id}","inheritsFrom":"1.20.1","libraries":[{{"name":"{library}"}}]}}"#
+			),
+		);
+		GameInstance::load(dir, id)
+	}
+
+	#[test]
+	fn test_check_optifine_addon() {
+		let dir = tempfile::tempdir().unwrap();
+		write(dir.path(), "1.20.1", "1.20.1.json", r#"{"id":"1.20.1"}"#);
+		let build = OptiFineBuild {
+			game_version: "1.20.1".into(),
+			edition: "HD_U".into(),
+			patch: "I6".into(),
+			filename: "OptiFine_1.20.1_HD_U_I6.jar".into(),
+			forge: Some("Forge 47.2.0".into()),
+		};
+
+		let fabric = child(dir.path(), "fabric", "net.fabricmc:fabric-loader:0.15.0");
+		assert!(check_optifine_addon(&fabric, &build).is_ok());
+		let forge = child(
+			dir.path(),
+			"forge",
+			"net.minecraftforge:forge:1.20.1-47.2.0",
+		);
+		assert!(check_optifine_addon(&forge, &build).is_ok());
+
+		let neoforge = child(
+			dir.path(),
+			"neoforge",
+			"net.neoforged:forge:1.20.1-47.1.106",
+		);
+		let err = check_optifine_addon(&neoforge, &build).unwrap_err();
+		assert!(err.to_string().contains("NeoForge"));
+		let vanilla = GameInstance::load(dir.path(), "1.20.1");
+		assert!(check_optifine_addon(&vanilla, &build).is_err());
+	}
+
+	#[tokio::test]
+	async fn test_failed_switch_restores_instance() {
+		let dir = tempfile::tempdir().unwrap();
+		let cluster = dir.path();
+		write(cluster, "1.20.1", "1.20.1.json", r#"{"id":"1.20.1"}"#);
+		child(cluster, "pack", "net.fabricmc:fabric-loader:0.15.0");
+		record_loader(
+			cluster,
+			"pack",
+			"1.20.1",
+			LoaderInfo {
+				kind: LoaderKind::Fabric,
+				version: "0.15.0".into(),
+			},
+		)
+		.unwrap();
+		let profile = std::fs::read_to_string(profile_path(cluster, "pack")).unwrap();
+		let meta = std::fs::read_to_string(InstanceMeta::path(cluster, "pack")).unwrap();
+
+		// 模拟写入了新的版本 JSON 与实例记录后才失败的安装
+		let failing = || async {
+			child(cluster, "pack", "net.minecraftforge:forge:1.20.1-47.2.0");
+			record_loader(
+				cluster,
+				"pack",
+				"1.20.1",
+				LoaderInfo {
+					kind: LoaderKind::Forge,
+					version: "47.2.0".into(),
+				},
+			)?;
+			Err(TaskError::Failed("processor failed".into()))
+		};
+		let result = with_rollback(cluster, "pack", failing()).await;
+		assert!(result.is_err());
+		assert_eq!(
+			std::fs::read_to_string(profile_path(cluster, "pack")).unwrap(),
+			profile
+		);
+		assert_eq!(
+			std::fs::read_to_string(InstanceMeta::path(cluster, "pack")).unwrap(),
+			meta
+		);
+
+		// 切换前没有实例记录时，失败后也不应留下记录
+		std::fs::remove_file(InstanceMeta::path(cluster, "pack")).unwrap();
+		let result = with_rollback(cluster, "pack", failing()).await;
+		assert!(result.is_err());
+		assert_eq!(
+			std::fs::read_to_string(profile_path(cluster, "pack")).unwrap(),
+			profile
+		);
+		assert!(!InstanceMeta::path(cluster, "pack").exists());
+	}
+}
@@ -1,2 +1,5 @@
+pub mod account;
 pub mod download;
+pub mod loader;
+pub mod runtime;
 pub mod start;
@@ -0,0 +1,386 @@
+use crate::infrastructure::network::download::{Checksum, DownloadClient, DownloadRequest};
+use crate::launcher::core::paths;
+use crate::launcher::core::state::AppState;
+use crate::launcher::task::error::{TaskError, TaskResult};
+use crate::launcher::task::framework::{ConcurrentTask, TaskContext, TaskType};
+use crate::launcher::task::lock::LockKey;
+use crate::minecraft::game::adoptium::{latest_release, unpack_archive};
+use crate::minecraft::game::java::{JavaInstallation, find_java, java_in_dir, runtimes_dir};
+use crate::minecraft::game::runtime::{
+	RUNTIME_MANIFEST, RemoteFile, RuntimeFile, RuntimeIndex, RuntimeManifest, create_link,
+	decompress_lzma, installed_version, mark_installed, platform_key, prune_runtime, runtime_dir,
+	set_executable,
+};
+use crate::minecraft::profile::VersionProfile;
+use crate::minecraft::tasks::download::ProgressRef;
+use futures_util::{StreamExt, TryStreamExt, stream};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs;
+use tokio::sync::watch;
+
+/// 安装 Mojang 官方 Java 运行时（如 `java-runtime-delta`），完成后加入 Java 清单
+pub struct InstallRuntimeTask {
+	pub component: String,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for InstallRuntimeTask {
+	const TYPE_NAME: &'static str = "install_runtime";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallRuntimeTask {
+	type Output = JavaInstallation;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource("install_runtime", &self.component)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let platform = platform_key().ok_or_else(|| {
+			TaskError::Failed("Mojang provides no Java runtime for this platform".into())
+		})?;
+		let index: RuntimeIndex = fetch_json(RUNTIME_MANIFEST).await?;
+		let entry = index
+			.get(platform)
+			.and_then(|components| components.get(&self.component))
+			.and_then(|entries| entries.first())
+			.ok_or_else(|| {
+				TaskError::Failed(format!(
+					"Runtime {} is not available for {}",
+					self.component, platform
+				))
+			})?;
+
+		let dir = runtime_dir(&self.component).map_err(|e| TaskError::Failed(e.to_string()))?;
+		if installed_version(&dir).as_deref() == Some(entry.version.name.as_str()) {
+			return register(&dir);
+		}
+
+		let message = format!("下载 Java 运行时 {}", self.component);
+		self.set_progress(&message, 0, None, false).await;
+
+		let manifest: RuntimeManifest = fetch_json(&entry.manifest.url).await?;
+		let client = Arc::new(DownloadClient::new().map_err(|e| TaskError::Failed(e.to_string()))?);
+		let cancel = ctx.cancelled_receiver();
+
+		// 重装时清掉新清单里已没有的旧文件
+		let keep: HashSet<PathBuf> = manifest.files.keys().map(|name| dir.join(name)).collect();
+		prune_runtime(&dir, &keep).map_err(|e| TaskError::Failed(e.to_string()))?;
+
+		let mut files = Vec::new();
+		let mut links = Vec::new();
+		for (name, file) in manifest.files {
+			let path = dir.join(&name);
+			match file {
+				RuntimeFile::Directory => fs::create_dir_all(&path)
+					.await
+					.map_err(|e| TaskError::Failed(e.to_string()))?,
+				RuntimeFile::File {
+					executable,
+					downloads,
+				} => files.push((path, executable, downloads.raw, downloads.lzma)),
+				RuntimeFile::Link { target } => links.push((path, target)),
+			}
+		}
+
+		let total: u64 = files
+			.iter()
+			.map(|(_, _, raw, lzma)| lzma.as_ref().unwrap_or(raw).size)
+			.sum();
+		let done = Arc::new(AtomicU64::new(0));
+		let concurrency = AppState::get().config.get().download_concurrency.max(1) as usize;
+
+		let mut downloads = stream::iter(files.into_iter().map(|(path, executable, raw, lzma)| {
+			let client = Arc::clone(&client);
+			let cancel = cancel.clone();
+			let done = Arc::clone(&done);
+			async move {
+				let size = lzma.as_ref().unwrap_or(&raw).size;
+				install_file(&client, &path, raw, lzma, cancel).await?;
+				if executable {
+					set_executable(&path).map_err(|e| TaskError::Failed(e.to_string()))?;
+				}
+				Ok::<u64, TaskError>(done.fetch_add(size, Ordering::Relaxed) + size)
+			}
+		}))
+		.buffer_unordered(concurrency);
+
+		while let Some(downloaded) = downloads.try_next().await? {
+			self.set_progress(&message, downloaded, Some(total), false)
+				.await;
+		}
+
+		for (path, target) in links {
+			if let Some(parent) = path.parent() {
+				fs::create_dir_all(parent)
+					.await
+					.map_err(|e| TaskError::Failed(e.to_string()))?;
+			}
+			create_link(&path, &target).map_err(|e| TaskError::Failed(e.to_string()))?;
+		}
+
+		mark_installed(&dir, &entry.version.name).map_err(|e| TaskError::Failed(e.to_string()))?;
+		self.set_progress(
+			&format!("Java 运行时 {} 安装完成", self.component),
+			total,
+			Some(total),
+			true,
+		)
+		.await;
+		register(&dir)
+	}
+}
+
+impl InstallRuntimeTask {
+	async fn set_progress(
+		&self,
+		message: &str,
+		downloaded: u64,
+		total: Option<u64>,
+		finished: bool,
+	) {
+		set_progress(&self.progress, message, downloaded, total, finished).await;
+	}
+}
+
+/// 从 Adoptium 下载 Temurin JDK，适用于 Mojang 运行时不覆盖的架构或需要完整 JDK 的情况
+pub struct InstallAdoptiumTask {
+	pub major: u32,
+	pub progress: Option<ProgressRef>,
+}
+
+impl TaskType for InstallAdoptiumTask {
+	const TYPE_NAME: &'static str = "install_adoptium";
+}
+
+#[async_trait::async_trait]
+impl ConcurrentTask for InstallAdoptiumTask {
+	type Output = JavaInstallation;
+
+	fn locks(&self) -> Vec<LockKey> {
+		vec![LockKey::resource(
+			"install_adoptium",
+			self.major.to_string(),
+		)]
+	}
+
+	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
+		let api = AppState::get().config.get().adoptium_api;
+		let release = latest_release(&api, self.major)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+
+		let dir = runtimes_dir()
+			.map_err(|e| TaskError::Failed(e.to_string()))?
+			.join(format!("temurin-{}", self.major));
+		if installed_version(&dir).as_deref() == Some(release.release_name.as_str()) {
+			return register(&dir);
+		}
+
+		let package = release.binary.package;
+		let message = format!("下载 {}", release.release_name);
+		let archive = paths::cache_dir()
+			.map_err(|e| TaskError::Failed(e.to_string()))?
+			.join(&package.name);
+
+		let client = DownloadClient::new().map_err(|e| TaskError::Failed(e.to_string()))?;
+		let progress = self.progress.clone();
+		let total = package.size;
+		client
+			.download(
+				DownloadRequest::new(package.link, &archive)
+					.with_checksum(Checksum::Sha256(package.checksum)),
+				|p| {
+					if let Some(progress) = &progress
+						&& let Ok(mut guard) = progress.try_write()
+					{
+						guard.message = message.clone();
+						guard.downloaded = p.downloaded;
+						guard.total = p.total.or(Some(total));
+						guard.speed_bps = p.speed_bps;
+					}
+				},
+				Some(ctx.cancelled_receiver()),
+			)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+
+		let unpacking = format!("解压 {}", release.release_name);
+		self.set_progress(&unpacking, total, Some(total), false)
+			.await;
+		let (src, dest) = (archive.clone(), dir.clone());
+		tokio::task::spawn_blocking(move || unpack_archive(&src, &dest))
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()))?
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let _ = fs::remove_file(&archive).await;
+
+		mark_installed(&dir, &release.release_name)
+			.map_err(|e| TaskError::Failed(e.to_string()))?;
+		let finished = format!("{} 安装完成", release.release_name);
+		self.set_progress(&finished, total, Some(total), true).await;
+		register(&dir)
+	}
+}
+
+impl InstallAdoptiumTask {
+	async fn set_progress(
+		&self,
+		message: &str,
+		downloaded: u64,
+		total: Option<u64>,
+		finished: bool,
+	) {
+		set_progress(&self.progress, message, downloaded, total, finished).await;
+	}
+}
+
+async fn set_progress(
+	progress: &Option<ProgressRef>,
+	message: &str,
+	downloaded: u64,
+	total: Option<u64>,
+	finished: bool,
+) {
+	if let Some(p) = progress {
+		let mut guard = p.write().await;
+		guard.message = message.to_string();
+		guard.downloaded = downloaded;
+		guard.total = total;
+		guard.finished = finished;
+	}
+}
+
+// 没有合适的 Java 时安装版本清单指定的官方运行时
+// Mojang 不提供运行时的平台改用 Adoptium
+pub async fn install_runtime(component: &str, major: u32) -> TaskResult<PathBuf> {
+	let tm = &AppState::get().task_manager;
+	let install = if platform_key().is_some() {
+		let task = InstallRuntimeTask {
+			component: component.to_string(),
+			progress: None,
+		};
+		tm.submit_concurrent(task)
+			.await
+			.map_err(|e| TaskError::Failed(format!("install runtime {component}: {e}")))?
+			.result()
+			.await?
+	} else {
+		let task = InstallAdoptiumTask {
+			major,
+			progress: None,
+		};
+		tm.submit_concurrent(task)
+			.await
+			.map_err(|e| TaskError::Failed(format!("install Temurin {major}: {e}")))?
+			.result()
+			.await?
+	};
+	Ok(install.path)
+}
+
+/// 为指定版本挑选 Java：`explicit`（实例设置）优先，其次是全局默认；
+/// 未指定 `explicit` 且没有合适的 Java 时自动安装
+pub async fn resolve_java(
+	profile: &VersionProfile,
+	explicit: Option<PathBuf>,
+) -> TaskResult<PathBuf> {
+	let state = AppState::get();
+	let auto_install = explicit.is_none();
+	match find_java(
+		explicit,
+		state.config.get().game.java_path,
+		profile.java_requirement(),
+		&state.java.list(),
+	) {
+		Ok(java) => Ok(java),
+		Err(e) => match profile.java_version.as_ref() {
+			Some(v) if auto_install && !v.component.is_empty() => {
+				tracing::info!("{}, installing runtime {}", e, v.component);
+				install_runtime(&v.component, v.major_version).await
+			}
+			_ => Err(TaskError::Failed(e.to_string())),
+		},
+	}
+}
+
+// 有 LZMA 版本时下载压缩包再解压，解压结果按原始文件的 SHA-1 校验
+async fn install_file(
+	client: &DownloadClient,
+	path: &Path,
+	raw: RemoteFile,
+	lzma: Option<RemoteFile>,
+	cancel: watch::Receiver<bool>,
+) -> TaskResult<()> {
+	let Some(lzma) = lzma else {
+		return client
+			.download(
+				DownloadRequest::new(raw.url, path).with_checksum(Checksum::Sha1(raw.sha1)),
+				|_| {},
+				Some(cancel),
+			)
+			.await
+			.map_err(|e| TaskError::Failed(e.to_string()));
+	};
+
+	if sha1_matches(path, &raw.sha1).await {
+		return Ok(());
+	}
+
+	let packed = path.with_extension("hako.lzma");
+	client
+		.download(
+			DownloadRequest::new(lzma.url, &packed).with_checksum(Checksum::Sha1(lzma.sha1)),
+			|_| {},
+			Some(cancel),
+		)
+		.await
+		.map_err(|e| TaskError::Failed(e.to_string()))?;
+
+	let (src, dest) = (packed.clone(), path.to_path_buf());
+	tokio::task::spawn_blocking(move || decompress_lzma(&src, &dest))
+		.await
+		.map_err(|e| TaskError::Failed(e.to_string()))?
+		.map_err(|e| TaskError::Failed(format!("{}: {e}", path.display())))?;
+	let _ = fs::remove_file(&packed).await;
+
+	if !sha1_matches(path, &raw.sha1).await {
+		return Err(TaskError::Failed(format!(
+			"checksum mismatch: {}",
+			path.display()
+		)));
+	}
+	Ok(())
+}
+
+async fn sha1_matches(path: &Path, expected: &str) -> bool {
+	use sha1::{Digest, Sha1};
+	match fs::read(path).await {
+		Ok(data) => hex::encode(Sha1::digest(&data)).eq_ignore_ascii_case(expected),
+		Err(_) => false,
+	}
+}
+
+fn register(dir: &Path) -> TaskResult<JavaInstallation> {
+	let java = java_in_dir(dir)
+		.ok_or_else(|| TaskError::Failed(format!("java not found in runtime {}", dir.display())))?;
+	let install = JavaInstallation::probe(&java).map_err(|e| TaskError::Failed(e.to_string()))?;
+	AppState::get().java.register(install.clone());
+	Ok(install)
+}
+
+async fn fetch_json<T: DeserializeOwned>(url: &str) -> TaskResult<T> {
+	let resp = reqwest::get(url)
+		.await
+		.map_err(|e| TaskError::Failed(format!("Fetch {url}: {e}")))?;
+	let text = resp
+		.text()
+		.await
+		.map_err(|e| TaskError::Failed(format!("Read {url}: {e}")))?;
+	serde_json::from_str(&text).map_err(|e| TaskError::Failed(format!("Parse {url}: {e}")))
+}
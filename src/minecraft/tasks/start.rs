@@ -1,15 +1,20 @@
 use crate::config::manager::ConfigManager;
 use crate::launcher::core::state::AppState;
-use crate::minecraft::game::args::{Features, collect_game_args, collect_jvm_args};
-use crate::minecraft::game::classpath::build_classpath;
-use crate::minecraft::game::instance::GameInstance;
-use crate::minecraft::game::java::find_java;
-use crate::minecraft::game::natives::{extract_natives, get_natives_directory};
-use crate::minecraft::profile::{VersionProfile, load_version_profile};
 use crate::launcher::task::error::{TaskError, TaskResult};
-use crate::launcher::task::lock::LockKey;
 use crate::launcher::task::framework::{BlockingTask, TaskContext, TaskType};
+use crate::launcher::task::lock::LockKey;
 use crate::launcher::task::sub_task::{SubTask, SubTaskChain, SubTaskContext};
+use crate::minecraft::account::{Account, AccountId, SessionState};
+use crate::minecraft::game::args::{Features, LaunchContext, collect_game_args, collect_jvm_args};
+use crate::minecraft::game::authlib::{AUTHLIB_INJECTOR_LATEST, authlib_injector_args};
+use crate::minecraft::game::classpath::build_classpath;
+use crate::minecraft::game::instance::GameInstance;
+use crate::minecraft::game::java::JavaInstallation;
+use crate::minecraft::game::jvm_compat::filter_jvm_args;
+use crate::minecraft::game::natives::{extract_natives, get_natives_directory};
+use crate::minecraft::profile::{VersionProfile, load_version_profile_with_report};
+use crate::minecraft::tasks::account::RefreshAccountTask;
+use crate::minecraft::tasks::runtime::resolve_java;
 use anyhow::Context;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -23,6 +28,7 @@ const MAX_WAIT_TIME: Duration = Duration::from_secs(30);
 struct StartContext {
 	game_dir: PathBuf,
 	version_id: String,
+	/// 实例单独指定的 Java，始终优先
 	java_path: Option<PathBuf>,
 	max_memory_mb: u32,
 	extra_jvm_args: Vec<String>,
@@ -34,28 +40,25 @@ struct StartContext {
 	classpath: Option<String>,
 	jvm_args: Vec<String>,
 	game_args: Vec<String>,
-	username: String,
-	uuid: String,
+	account: Account,
+	/// 为兼容所选 Java 而调整的 JVM 参数
+	warnings: Vec<String>,
 }
 
 impl StartContext {
-	fn from_instance(instance: &GameInstance) -> Self {
+	async fn from_instance(
+		instance: &GameInstance,
+		launch_override: Option<AccountId>,
+	) -> TaskResult<Self> {
 		let state = AppState::get();
 		let launcher_config = state.config.get();
 		let game_config =
 			ConfigManager::load_game_config(&instance.cluster_path, &instance.version);
 		let resolved = game_config.resolve(&launcher_config.game);
-
-		let (username, uuid) = state
-			.accounts
-			.current()
-			.map(|a| (a.username().to_string(), a.uuid().to_string()))
-			.unwrap_or_else(|| {
-				(
-					"Player".into(),
-					crate::minecraft::account::offline_uuid("Player").to_string(),
-				)
-			});
+		let account = match resolve_account(instance, launch_override, resolved.account)? {
+			Some(id) => ensure_session(id).await?,
+			None => Account::offline("Player"),
+		};
 
 		let jvm_args: Vec<String> = resolved
 			.jvm_args
@@ -68,10 +71,10 @@ impl StartContext {
 			.map(String::from)
 			.collect();
 
-		Self {
+		Ok(Self {
 			game_dir: instance.cluster_path.clone(),
 			version_id: instance.version.clone(),
-			java_path: resolved.java_path,
+			java_path: game_config.java_path.clone(),
 			max_memory_mb: resolved.max_memory_mb,
 			extra_jvm_args: jvm_args,
 			extra_game_args: game_args,
@@ -81,14 +84,16 @@ impl StartContext {
 			classpath: None,
 			jvm_args: Vec::new(),
 			game_args: Vec::new(),
-			username,
-			uuid,
-		}
+			account,
+			warnings: Vec::new(),
+		})
 	}
 }
 
 pub struct StartGameTask {
 	pub instance: GameInstance,
+	/// 本次启动使用的账户，优先于实例绑定和当前选中的账户
+	pub account: Option<AccountId>,
 }
 
 impl TaskType for StartGameTask {
@@ -97,7 +102,8 @@ impl TaskType for StartGameTask {
 
 #[async_trait::async_trait]
 impl BlockingTask for StartGameTask {
-	type Output = ();
+	/// 启动时被过滤或改写的 JVM 参数说明
+	type Output = Vec<String>;
 
 	fn locks(&self) -> Vec<LockKey> {
 		vec![LockKey::global("start_game")]
@@ -108,14 +114,79 @@ impl BlockingTask for StartGameTask {
 	}
 
 	async fn execute(&mut self, ctx: &TaskContext) -> TaskResult<Self::Output> {
-		let shared = Arc::new(RwLock::new(StartContext::from_instance(&self.instance)));
+		let shared = Arc::new(RwLock::new(
+			StartContext::from_instance(&self.instance, self.account).await?,
+		));
 
 		let mut chain = SubTaskChain::new();
 		chain.add(PrepareEnvTask(Arc::clone(&shared)));
-		chain.add(LaunchTask(shared));
+		chain.add(LaunchTask(Arc::clone(&shared)));
 
 		let sub_ctx = SubTaskContext::new(ctx.cancelled_receiver());
-		chain.execute(&sub_ctx).await
+		chain.execute(&sub_ctx).await?;
+		Ok(std::mem::take(&mut shared.write().await.warnings))
+	}
+}
+
+// 启动参数 > 实例绑定 > 当前账户；显式指定的账户不存在时报错，不回退到默认账户
+fn resolve_account(
+	instance: &GameInstance,
+	launch_override: Option<AccountId>,
+	bound: Option<AccountId>,
+) -> TaskResult<Option<AccountId>> {
+	let accounts = &AppState::get().accounts;
+	if let Some(id) = launch_override {
+		return match accounts.get(id) {
+			Some(_) => Ok(Some(id)),
+			None => Err(TaskError::Failed(format!(
+				"Account {id} selected for this launch does not exist"
+			))),
+		};
+	}
+
+	if let Some(id) = bound {
+		return match accounts.get(id) {
+			Some(_) => Ok(Some(id)),
+			None => Err(TaskError::Failed(format!(
+				"Account {id} bound to instance {} has been removed, rebind or clear it in the instance settings",
+				instance.version
+			))),
+		};
+	}
+
+	Ok(accounts.current_id())
+}
+
+// 启动前刷新账户令牌，失效的会话直接报错而不是带着无效令牌启动
+async fn ensure_session(id: AccountId) -> TaskResult<Account> {
+	let state = AppState::get();
+
+	let task = RefreshAccountTask {
+		account: id,
+		force: false,
+	};
+	match state.task_manager.submit_concurrent(task).await {
+		Ok(mut h) => {
+			if let Err(e) = h.result().await {
+				tracing::warn!("Refresh before launch failed: {}", e);
+			}
+		}
+		Err(e) => tracing::warn!("Refresh before launch skipped: {}", e),
+	}
+
+	let Some(account) = state.accounts.get(id) else {
+		return Err(TaskError::Failed(format!("Account {id} was removed")));
+	};
+	match account.session_state() {
+		SessionState::Valid => Ok(account),
+		SessionState::Expired => Err(TaskError::Failed(format!(
+			"Session of {} expired and could not be refreshed",
+			account.username()
+		))),
+		SessionState::Revoked => Err(TaskError::Failed(format!(
+			"Session of {} was revoked, please log in again",
+			account.username()
+		))),
 	}
 }
 
@@ -123,21 +194,32 @@ struct PrepareEnvTask(Arc<RwLock<StartContext>>);
 
 #[async_trait::async_trait]
 impl SubTask for PrepareEnvTask {
-	async fn execute(&self, _ctx: &SubTaskContext) -> Result<(), TaskError> {
+	async fn execute(&self, ctx: &SubTaskContext) -> Result<(), TaskError> {
 		let mut s = self.0.write().await;
 
-		let profile = load_version_profile(&s.game_dir, &s.version_id)
+		let (profile, report) = load_version_profile_with_report(&s.game_dir, &s.version_id)
 			.map_err(|e| TaskError::Failed(format!("load profile: {e}")))?;
+		for o in &report.overridden {
+			tracing::debug!("Library {} overridden by {}", o.parent, o.child);
+		}
 
 		let natives_dir = get_natives_directory(&s.game_dir, &s.version_id)
 			.map_err(|e| TaskError::Failed(e.to_string()))?;
 
-		let features = Features::default();
+		let features = Features {
+			is_demo_user: s.account.is_demo(),
+			..Default::default()
+		};
+		if features.is_demo_user {
+			tracing::info!(
+				"{} does not own the game, launching in demo mode",
+				s.account.username()
+			);
+		}
 		extract_natives(&s.game_dir, &profile, &natives_dir, &features)
 			.map_err(|e| TaskError::Failed(e.to_string()))?;
 
-		let java_bin =
-			find_java(s.java_path.take()).map_err(|e| TaskError::Failed(e.to_string()))?;
+		let java_bin = resolve_java(&profile, s.java_path.take()).await?;
 
 		let cp = build_classpath(&s.game_dir, &s.version_id, &profile, &features)
 			.map_err(|e| TaskError::Failed(e.to_string()))?;
@@ -148,29 +230,26 @@ impl SubTask for PrepareEnvTask {
 			.unwrap_or(&s.version_id)
 			.to_string();
 
-		let mut jvm_args = collect_jvm_args(
-			&profile,
-			&s.game_dir,
-			&s.version_id,
-			&cp,
-			&assets_index,
-			&s.username,
-			&s.uuid,
-			&natives_dir,
-			&features,
-		);
+		let launch = LaunchContext {
+			game_dir: &s.game_dir,
+			version: &s.version_id,
+			assets_index: &assets_index,
+			account: &s.account,
+			features: &features,
+		};
+		let mut jvm_args = collect_jvm_args(&profile, &launch, &cp, &natives_dir);
 
 		jvm_args.insert(0, format!("-Xmx{}M", s.max_memory_mb));
 
-		let game_args = collect_game_args(
-			&s.game_dir,
-			&s.version_id,
-			&profile,
-			&s.username,
-			&s.uuid,
-			&assets_index,
-			&features,
-		);
+		if let Account::Yggdrasil { server, .. } = &s.account {
+			let agent_args =
+				authlib_injector_args(server, AUTHLIB_INJECTOR_LATEST, Some(ctx.cancelled.clone()))
+					.await
+					.map_err(|e| TaskError::Failed(format!("authlib-injector: {e}")))?;
+			jvm_args.splice(1..1, agent_args);
+		}
+
+		let game_args = collect_game_args(&profile, &launch);
 
 		s.profile = Some(profile);
 		s.natives_dir = Some(natives_dir);
@@ -178,6 +257,17 @@ impl SubTask for PrepareEnvTask {
 		s.classpath = Some(cp);
 		s.jvm_args = jvm_args;
 		let extra_jvm = std::mem::take(&mut s.extra_jvm_args);
+		let extra_jvm = match java_installation(s.java_bin.as_deref()) {
+			Some(java) => {
+				let (args, warnings) = filter_jvm_args(extra_jvm, &java);
+				for w in &warnings {
+					tracing::warn!("{}", w);
+				}
+				s.warnings = warnings;
+				args
+			}
+			None => extra_jvm,
+		};
 		s.jvm_args.extend(extra_jvm);
 		s.game_args = game_args;
 		let extra_game = std::mem::take(&mut s.extra_game_args);
@@ -187,6 +277,25 @@ impl SubTask for PrepareEnvTask {
 	}
 }
 
+// 清单里没有时现场探测，探测失败则不做过滤
+fn java_installation(java: Option<&std::path::Path>) -> Option<JavaInstallation> {
+	let java = java?;
+	AppState::get()
+		.java
+		.find(java)
+		.or_else(|| match JavaInstallation::probe(java) {
+			Ok(install) => Some(install),
+			Err(e) => {
+				tracing::warn!(
+					"Cannot probe {}: {}, JVM arguments left unchecked",
+					java.display(),
+					e
+				);
+				None
+			}
+		})
+}
+
 struct LaunchTask(Arc<RwLock<StartContext>>);
 
 #[async_trait::async_trait]
@@ -223,6 +332,7 @@ impl SubTask for LaunchTask {
 			.stdout(std::process::Stdio::piped())
 			.stderr(std::process::Stdio::piped());
 
+		let warnings = s.warnings.clone();
 		let mut child = cmd
 			.spawn()
 			.context("spawn game process")
@@ -243,15 +353,17 @@ impl SubTask for LaunchTask {
 		let mut stderr_lines = tokio::io::BufReader::new(stderr).lines();
 		let mut cancelled = ctx.cancelled.clone();
 		let start = Instant::now();
+		// JVM 启动失败时的报错只会出现在 stderr，留着拼进错误信息
+		let mut jvm_error: Option<String> = None;
 
 		loop {
 			tokio::select! {
 				line = stdout_lines.next_line() => {
-					if let Ok(Some(log)) = line {
-						if is_game_initialized(&log) {
-							tracing::info!("game initialized");
-							return Ok(());
-						}
+					if let Ok(Some(log)) = line
+						&& is_game_initialized(&log)
+					{
+						tracing::info!("game initialized");
+						return Ok(());
 					}
 				}
 				line = stderr_lines.next_line() => {
@@ -260,12 +372,22 @@ impl SubTask for LaunchTask {
 							tracing::info!("game initialized");
 							return Ok(());
 						}
+						if jvm_error.is_none() && is_jvm_error(&log) {
+							jvm_error = Some(log);
+						}
 					}
 				}
 				_ = tokio::time::sleep(Duration::from_millis(100)) => {
 					if let Ok(Some(status)) = child.try_wait() {
 						if !status.success() {
-							return Err(TaskError::Failed(format!("Game exited: {:?}", status.code())));
+							let mut msg = format!("Game exited: {:?}", status.code());
+							if let Some(e) = &jvm_error {
+								msg.push_str(&format!("; {e}"));
+							}
+							if !warnings.is_empty() {
+								msg.push_str(&format!("; adjusted JVM arguments: {}", warnings.join("; ")));
+							}
+							return Err(TaskError::Failed(msg));
 						}
 						return Ok(());
 					}
@@ -287,3 +409,10 @@ fn is_game_initialized(log: &str) -> bool {
 	let l = log.to_lowercase();
 	l.contains("lwjgl version") || l.contains("openal initialized") || l.contains("setting user:")
 }
+
+fn is_jvm_error(log: &str) -> bool {
+	log.starts_with("Unrecognized VM option")
+		|| log.starts_with("Unrecognized option")
+		|| log.starts_with("Error: Could not create the Java Virtual Machine")
+		|| log.contains("Error occurred during initialization of VM")
+}
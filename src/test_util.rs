@@ -0,0 +1,62 @@
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// 启动本地 HTTP 服务，`handler` 收到完整请求文本，返回状态行与响应体，返回服务地址
+pub async fn serve<F>(mut handler: F) -> String
+where
+	F: FnMut(&str) -> (&'static str, String) + Send + 'static,
+{
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let url = format!("http://{}", listener.local_addr().unwrap());
+
+	tokio::spawn(async move {
+		loop {
+			let (mut socket, _) = listener.accept().await.unwrap();
+			let req = read_request(&mut socket).await;
+			let (status, body) = handler(&req);
+			let response = format!(
+				"HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+				status,
+				body.len(),
+				body
+			);
+			socket.write_all(response.as_bytes()).await.unwrap();
+		}
+	});
+
+	url
+}
+
+/// 请求行中的路径，如 `/v2/versions/loader/1.20.1`
+pub fn request_path(req: &str) -> &str {
+	req.split_whitespace().nth(1).unwrap_or("")
+}
+
+// 按 Content-Length 读完请求体
+async fn read_request(socket: &mut TcpStream) -> String {
+	let mut data = Vec::new();
+	let mut buf = [0u8; 4096];
+	loop {
+		let n = socket.read(&mut buf).await.unwrap();
+		if n == 0 {
+			break;
+		}
+		data.extend_from_slice(&buf[..n]);
+		let text = String::from_utf8_lossy(&data);
+		if let Some(end) = text.find("\r\n\r\n") {
+			let len = text[..end]
+				.lines()
+				.find_map(|l| {
+					let (k, v) = l.split_once(':')?;
+					k.eq_ignore_ascii_case("content-length")
+						.then(|| v.trim().parse::<usize>().ok())
+						.flatten()
+				})
+				.unwrap_or(0);
+			if data.len() >= end + 4 + len {
+				break;
+			}
+		}
+	}
+	String::from_utf8_lossy(&data).into_owned()
+}
@@ -1,5 +1,5 @@
 use crate::launcher::core::state::AppState;
-use crate::ui::components::{navbar::Navbar, topbar::Topbar};
+use crate::ui::components::{account_form::AccountForm, navbar::Navbar, topbar::Topbar};
 use crate::ui::views::{
 	download::DownloadView, home::HomeView, instances::InstancesView, settings::SettingsView,
 	tasks::TasksView,
@@ -10,6 +10,7 @@ use gpui_router::{Route, Routes};
 pub struct HakoApp {
 	topbar: Entity<Topbar>,
 	navbar: Entity<Navbar>,
+	account_form: Entity<AccountForm>,
 }
 
 impl HakoApp {
@@ -19,12 +20,14 @@ impl HakoApp {
 		Self {
 			topbar: ctx.new(|_| Topbar::new()),
 			navbar: ctx.new(|cx| Navbar::new(cx)),
+			account_form: ctx.new(AccountForm::new),
 		}
 	}
 }
 
 impl Render for HakoApp {
 	fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+		let account_form = self.account_form.clone();
 		div()
 			.flex()
 			.flex_col()
@@ -57,9 +60,9 @@ impl Render for HakoApp {
 									.element(|_, _| TasksView::render()),
 							)
 							.child(
-								Route::new()
-									.path("settings")
-									.element(|_, _| SettingsView::render()),
+								Route::new().path("settings").element(move |_, _| {
+									SettingsView::render(account_form.clone())
+								}),
 							),
 					),
 			)
@@ -0,0 +1,156 @@
+use crate::launcher::core::state::AppState;
+use crate::minecraft::account::offline::{OfflineUuid, OfflineUuidScheme};
+use crate::minecraft::tasks::account::LoginYggdrasilTask;
+use crate::ui::components::action_button::ActionButton;
+use crate::ui::components::text_field::TextField;
+use crate::ui::views::tasks::TasksView;
+use gpui::{Context, Entity, Window, div, prelude::*, rgb};
+use uuid::Uuid;
+
+/// 设置页中添加账户的表单
+pub struct AccountForm {
+	offline_name: Entity<TextField>,
+	scheme: OfflineUuidScheme,
+	custom_uuid: Entity<TextField>,
+	server: Entity<TextField>,
+	username: Entity<TextField>,
+	password: Entity<TextField>,
+}
+
+impl AccountForm {
+	pub fn new(cx: &mut Context<Self>) -> Self {
+		Self {
+			offline_name: cx.new(|cx| TextField::new("玩家名", cx)),
+			scheme: OfflineUuidScheme::Vanilla,
+			custom_uuid: cx.new(|cx| TextField::new("UUID", cx)),
+			server: cx.new(|cx| TextField::new("认证服务器地址", cx)),
+			username: cx.new(|cx| TextField::new("邮箱或用户名", cx)),
+			password: cx.new(|cx| TextField::new("密码", cx).masked()),
+		}
+	}
+
+	fn render_offline(&self, cx: &mut Context<Self>) -> impl IntoElement {
+		let (name, custom) = (self.offline_name.clone(), self.custom_uuid.clone());
+		let scheme = self.scheme;
+		let mut row = Self::render_row("离线账户", "原版 UUID 与服务器离线模式一致")
+			.child(self.offline_name.clone());
+		for (option, label) in [
+			(OfflineUuidScheme::Vanilla, "原版"),
+			(OfflineUuidScheme::Legacy, "旧版"),
+			(OfflineUuidScheme::Custom, "自定义"),
+		] {
+			row = row.child(
+				div()
+					.px_3()
+					.py_1()
+					.rounded_md()
+					.bg(if option == scheme {
+						rgb(0x1e3a5f)
+					} else {
+						rgb(0x1a1a1a)
+					})
+					.text_color(rgb(0x888888))
+					.text_sm()
+					.cursor_pointer()
+					.on_mouse_down(
+						gpui::MouseButton::Left,
+						cx.listener(move |this, _, _, cx| {
+							this.scheme = option;
+							cx.notify();
+						}),
+					)
+					.child(label),
+			);
+		}
+		row.when(scheme == OfflineUuidScheme::Custom, |d| {
+			d.child(self.custom_uuid.clone())
+		})
+		.child(ActionButton::render("添加", move |cx| {
+			let username = name.read(cx).text().to_string();
+			if username.is_empty() {
+				tracing::error!("请填写玩家名");
+				return;
+			}
+			let uuid = match scheme {
+				OfflineUuidScheme::Vanilla => OfflineUuid::Vanilla,
+				OfflineUuidScheme::Legacy => OfflineUuid::Legacy,
+				OfflineUuidScheme::Custom => match Uuid::parse_str(custom.read(cx).text()) {
+					Ok(uuid) => OfflineUuid::Custom(uuid),
+					Err(e) => {
+						tracing::error!("UUID 格式错误: {}", e);
+						return;
+					}
+				},
+			};
+			AppState::get().accounts.add_offline_with(&username, uuid);
+			tracing::info!("已添加离线账户 {}", username);
+			name.update(cx, |f, cx| f.clear(cx));
+			custom.update(cx, |f, cx| f.clear(cx));
+		}))
+	}
+
+	fn render_yggdrasil(&self) -> impl IntoElement {
+		let (server, username, password) = (
+			self.server.clone(),
+			self.username.clone(),
+			self.password.clone(),
+		);
+		Self::render_row("外置登录", "authlib-injector 兼容的认证服务器")
+			.child(self.server.clone())
+			.child(self.username.clone())
+			.child(self.password.clone())
+			.child(ActionButton::render("登录", move |cx| {
+				let task = LoginYggdrasilTask {
+					server: server.read(cx).text().to_string(),
+					username: username.read(cx).text().to_string(),
+					password: password.read(cx).text().to_string(),
+					progress: None,
+				};
+				if task.server.is_empty() || task.username.is_empty() || task.password.is_empty() {
+					tracing::error!("请填写认证服务器地址、用户名与密码");
+					return;
+				}
+				password.update(cx, |f, cx| f.clear(cx));
+				TasksView::spawn(format!("登录 {}", task.server), move |progress| {
+					LoginYggdrasilTask {
+						progress: Some(progress),
+						..task
+					}
+				});
+			}))
+	}
+
+	fn render_row(label: &str, desc: &str) -> gpui::Div {
+		div()
+			.flex()
+			.flex_wrap()
+			.items_center()
+			.gap_2()
+			.py_2()
+			.child(
+				div()
+					.flex()
+					.flex_col()
+					.gap_1()
+					.flex_grow()
+					.child(div().text_color(rgb(0xdddddd)).child(label.to_string()))
+					.child(
+						div()
+							.text_xs()
+							.text_color(rgb(0x666666))
+							.child(desc.to_string()),
+					),
+			)
+	}
+}
+
+impl Render for AccountForm {
+	fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+		div()
+			.flex()
+			.flex_col()
+			.gap_2()
+			.child(self.render_offline(cx))
+			.child(self.render_yggdrasil())
+	}
+}
@@ -0,0 +1,20 @@
+use gpui::{App, div, prelude::*, rgb};
+
+/// 面板中的小号操作按钮
+pub struct ActionButton;
+
+impl ActionButton {
+	pub fn render(label: &str, on_click: impl Fn(&mut App) + 'static) -> impl IntoElement {
+		div()
+			.px_3()
+			.py_1()
+			.rounded_md()
+			.bg(rgb(0x1a1a1a))
+			.text_color(rgb(0x888888))
+			.text_sm()
+			.hover(|s| s.bg(rgb(0x252525)))
+			.cursor_pointer()
+			.on_mouse_down(gpui::MouseButton::Left, move |_, _, cx| on_click(cx))
+			.child(label.to_string())
+	}
+}
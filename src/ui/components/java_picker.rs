@@ -0,0 +1,84 @@
+use crate::launcher::core::state::AppState;
+use gpui::{div, prelude::*, rgb};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// 从 Java 清单中选择运行时，`None` 表示自动选择
+pub struct JavaPicker;
+
+impl JavaPicker {
+	pub fn render(
+		selected: Option<PathBuf>,
+		on_select: impl Fn(Option<PathBuf>) + 'static,
+	) -> impl IntoElement {
+		let on_select: Rc<dyn Fn(Option<PathBuf>)> = Rc::new(on_select);
+		let installs = AppState::get().java.list();
+
+		let mut options = vec![(
+			None,
+			"自动选择".to_string(),
+			"按游戏版本要求挑选".to_string(),
+		)];
+		options.extend(installs.into_iter().map(|i| {
+			(
+				Some(i.path.clone()),
+				i.display_name(),
+				i.home.display().to_string(),
+			)
+		}));
+		// 配置中的路径不在清单里（已卸载或尚未扫描）时仍然显示出来
+		if let Some(path) = &selected
+			&& !options.iter().any(|(p, ..)| p.as_ref() == Some(path))
+		{
+			options.push((
+				Some(path.clone()),
+				"未识别的 Java".to_string(),
+				path.display().to_string(),
+			));
+		}
+
+		div()
+			.flex()
+			.flex_col()
+			.gap_2()
+			.children(options.into_iter().map(|(path, name, desc)| {
+				let is_sel = path == selected;
+				let on_select = Rc::clone(&on_select);
+				div()
+					.flex()
+					.flex_col()
+					.gap_1()
+					.px_3()
+					.py_2()
+					.rounded_md()
+					.bg(if is_sel { rgb(0x1e3a5f) } else { rgb(0x1a1a1a) })
+					.border_1()
+					.border_color(if is_sel { rgb(0x3b82f6) } else { rgb(0x333333) })
+					.hover(|s| s.bg(rgb(0x252525)))
+					.cursor_pointer()
+					.on_mouse_down(gpui::MouseButton::Left, move |_, _, _| {
+						on_select(path.clone());
+					})
+					.child(div().text_color(rgb(0xdddddd)).child(name))
+					.child(div().text_xs().text_color(rgb(0x666666)).child(desc))
+			}))
+	}
+
+	pub fn render_rescan() -> impl IntoElement {
+		div()
+			.px_3()
+			.py_1()
+			.rounded_md()
+			.bg(rgb(0x1a1a1a))
+			.text_color(rgb(0x888888))
+			.text_sm()
+			.hover(|s| s.bg(rgb(0x252525)))
+			.cursor_pointer()
+			.on_mouse_down(gpui::MouseButton::Left, |_, _, _| {
+				tokio::runtime::Handle::current().spawn_blocking(|| {
+					AppState::get().java.refresh();
+				});
+			})
+			.child("重新扫描")
+	}
+}
@@ -18,12 +18,19 @@ impl Navbar {
 		let tm = state.task_manager.clone();
 		let ver = inst.version.clone();
 		tokio::runtime::Handle::current().spawn(async move {
-			let task = StartGameTask { instance: inst };
+			let task = StartGameTask {
+				instance: inst,
+				account: None,
+			};
 			match tm.submit_blocking(task).await {
 				Ok(mut h) => {
 					tracing::info!("启动: {} ({})", ver, h.id);
 					tokio::spawn(async move {
-						let _ = h.result().await;
+						if let Ok(warnings) = h.result().await {
+							for w in warnings {
+								tracing::warn!("{}", w);
+							}
+						}
 					});
 				}
 				Err(e) => tracing::error!("启动失败: {}", e),
@@ -0,0 +1,93 @@
+use gpui::{Context, FocusHandle, KeyDownEvent, SharedString, Window, div, prelude::*, px, rgb};
+
+/// 单行文本输入框，只处理直接键入、退格与粘贴，不接入输入法
+pub struct TextField {
+	text: String,
+	placeholder: SharedString,
+	/// 密码框只显示圆点
+	masked: bool,
+	focus: FocusHandle,
+}
+
+impl TextField {
+	pub fn new(placeholder: impl Into<SharedString>, cx: &mut Context<Self>) -> Self {
+		Self {
+			text: String::new(),
+			placeholder: placeholder.into(),
+			masked: false,
+			focus: cx.focus_handle(),
+		}
+	}
+
+	pub fn masked(mut self) -> Self {
+		self.masked = true;
+		self
+	}
+
+	pub fn text(&self) -> &str {
+		self.text.trim()
+	}
+
+	pub fn clear(&mut self, cx: &mut Context<Self>) {
+		self.text.clear();
+		cx.notify();
+	}
+
+	fn on_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+		let keystroke = &event.keystroke;
+		if keystroke.modifiers.secondary() {
+			if keystroke.key != "v" {
+				return;
+			}
+			let Some(text) = cx.read_from_clipboard().and_then(|c| c.text()) else {
+				return;
+			};
+			self.text.extend(text.chars().filter(|c| !c.is_control()));
+		} else if keystroke.key == "backspace" {
+			self.text.pop();
+		} else if let Some(input) = &keystroke.key_char
+			&& !input.chars().any(char::is_control)
+		{
+			self.text.push_str(input);
+		} else {
+			return;
+		}
+		cx.notify();
+	}
+}
+
+impl Render for TextField {
+	fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+		let focused = self.focus.is_focused(window);
+		let (content, color) = if self.text.is_empty() {
+			(self.placeholder.to_string(), rgb(0x555555))
+		} else if self.masked {
+			("•".repeat(self.text.chars().count()), rgb(0xdddddd))
+		} else {
+			(self.text.clone(), rgb(0xdddddd))
+		};
+
+		div()
+			.track_focus(&self.focus)
+			.on_key_down(cx.listener(Self::on_key_down))
+			.on_mouse_down(
+				gpui::MouseButton::Left,
+				cx.listener(|this, _, window, _| window.focus(&this.focus)),
+			)
+			.min_w(px(160.))
+			.px_3()
+			.py_1()
+			.rounded_md()
+			.bg(rgb(0x1a1a1a))
+			.border_1()
+			.border_color(if focused {
+				rgb(0x3b82f6)
+			} else {
+				rgb(0x333333)
+			})
+			.text_sm()
+			.text_color(color)
+			.cursor_text()
+			.child(content)
+	}
+}
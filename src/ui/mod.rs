@@ -2,7 +2,11 @@ use gpui::{App, TitlebarOptions, WindowBounds, WindowOptions, px};
 
 pub mod app;
 pub mod components {
+	pub mod account_form;
+	pub mod action_button;
+	pub mod java_picker;
 	pub mod navbar;
+	pub mod text_field;
 	pub mod topbar;
 }
 pub mod views;
@@ -1,4 +1,14 @@
+use crate::config::manager::ConfigManager;
 use crate::launcher::core::state::AppState;
+use crate::minecraft::game::instance::GameInstance;
+use crate::minecraft::loader::{LoaderInfo, LoaderKind};
+use crate::minecraft::tasks::loader::{
+	InstallFabricTask, InstallForgeTask, InstallNeoForgeTask, InstallOptiFineTask,
+	InstallQuiltTask, OptiFineTarget, SwitchLoaderTask, rollback_loader,
+};
+use crate::ui::components::action_button::ActionButton;
+use crate::ui::components::java_picker::JavaPicker;
+use crate::ui::views::tasks::TasksView;
 use gpui::{div, prelude::*, rgb};
 
 pub struct InstancesView;
@@ -9,6 +19,7 @@ impl InstancesView {
 		let instances = state.instances.read().unwrap().clone();
 		let current_idx = *state.current_instance.lock().unwrap();
 		let cluster_path = state.cluster_path();
+		let selected = current_idx.and_then(|idx| instances.get(idx).cloned());
 
 		div()
 			.flex()
@@ -56,6 +67,10 @@ impl InstancesView {
 						let is_sel = current_idx == Some(idx);
 						let ver = inst.version.clone();
 						let path = inst.version_path.display().to_string();
+						let summary = Self::summary(&inst);
+						let issues: Vec<String> =
+							inst.issues.iter().map(|i| i.to_string()).collect();
+						let healthy = inst.is_healthy();
 
 						div()
 							.flex()
@@ -94,12 +109,296 @@ impl InstancesView {
 														.text_xs()
 														.child("当前"),
 												)
+											})
+											.when(!healthy, |d| {
+												d.child(
+													div()
+														.px_2()
+														.py_1()
+														.rounded_sm()
+														.bg(rgb(0x7f1d1d))
+														.text_color(rgb(0xffffff))
+														.text_xs()
+														.child("需要修复"),
+												)
 											}),
 									)
+									.when_some(summary, |d, summary| {
+										d.child(
+											div()
+												.text_sm()
+												.text_color(rgb(0x888888))
+												.child(summary),
+										)
+									})
+									.children(issues.into_iter().map(|issue| {
+										div().text_sm().text_color(rgb(0xef4444)).child(issue)
+									}))
 									.child(div().text_sm().text_color(rgb(0x666666)).child(path)),
 							)
 					}))
 					.into_any_element(),
 			})
+			.when_some(selected.clone(), |d, inst| d.child(Self::render_java(inst)))
+			.when_some(selected, |d, inst| d.child(Self::render_loader(inst)))
+	}
+
+	fn render_loader(inst: GameInstance) -> impl IntoElement {
+		let current = match (&inst.loader, &inst.game_version) {
+			(Some(loader), _) => format!("{} {}", loader.kind, loader.version),
+			(None, Some(game)) => format!("原版 {game}"),
+			(None, None) => "无法识别".to_string(),
+		};
+		let mut actions = div().flex().flex_wrap().gap_2();
+		// 原版实例上安装加载器会生成新的实例
+		if let Some(game) = inst.game_version.clone()
+			&& inst.loader.is_none()
+			&& game == inst.version
+		{
+			let (cluster_path, game_version) = (inst.cluster_path.clone(), game.clone());
+			actions = actions.child(ActionButton::render("安装 Fabric", move |_| {
+				let (cluster_path, game_version) = (cluster_path.clone(), game_version.clone());
+				TasksView::spawn(format!("安装 Fabric {game_version}"), move |progress| {
+					InstallFabricTask {
+						cluster_path,
+						game_version,
+						loader_version: None,
+						name: None,
+						progress: Some(progress),
+					}
+				});
+			}));
+			let (cluster_path, game_version) = (inst.cluster_path.clone(), game.clone());
+			actions = actions.child(ActionButton::render("安装 Quilt", move |_| {
+				let (cluster_path, game_version) = (cluster_path.clone(), game_version.clone());
+				TasksView::spawn(format!("安装 Quilt {game_version}"), move |progress| {
+					InstallQuiltTask {
+						cluster_path,
+						game_version,
+						loader_version: None,
+						name: None,
+						progress: Some(progress),
+					}
+				});
+			}));
+			let (cluster_path, game_version) = (inst.cluster_path.clone(), game.clone());
+			actions = actions.child(ActionButton::render("安装 Forge", move |_| {
+				let (cluster_path, game_version) = (cluster_path.clone(), game_version.clone());
+				TasksView::spawn(format!("安装 Forge {game_version}"), move |progress| {
+					InstallForgeTask {
+						cluster_path,
+						game_version,
+						forge_version: None,
+						name: None,
+						progress: Some(progress),
+					}
+				});
+			}));
+			let (cluster_path, game_version) = (inst.cluster_path.clone(), game.clone());
+			actions = actions.child(ActionButton::render("安装 NeoForge", move |_| {
+				let (cluster_path, game_version) = (cluster_path.clone(), game_version.clone());
+				TasksView::spawn(format!("安装 NeoForge {game_version}"), move |progress| {
+					InstallNeoForgeTask {
+						cluster_path,
+						game_version,
+						neoforge_version: None,
+						name: None,
+						progress: Some(progress),
+					}
+				});
+			}));
+			let (cluster_path, game_version) = (inst.cluster_path.clone(), game.clone());
+			actions = actions.child(ActionButton::render("安装 OptiFine", move |_| {
+				let (cluster_path, game_version) = (cluster_path.clone(), game_version.clone());
+				TasksView::spawn(format!("安装 OptiFine {game_version}"), move |progress| {
+					InstallOptiFineTask {
+						cluster_path,
+						game_version,
+						build: None,
+						target: OptiFineTarget::Standalone { name: None },
+						progress: Some(progress),
+					}
+				});
+			}));
+		}
+		if inst
+			.loader
+			.as_ref()
+			.is_some_and(|l| l.kind == LoaderKind::NeoForge)
+		{
+			let inst = inst.clone();
+			actions = actions.child(ActionButton::render("升级 NeoForge", move |_| {
+				let label = format!("升级 NeoForge {}", inst.version);
+				match InstallNeoForgeTask::upgrade(&inst, None, None) {
+					Ok(task) => TasksView::spawn(label, move |progress| InstallNeoForgeTask {
+						progress: Some(progress),
+						..task
+					}),
+					Err(e) => tracing::error!("{}失败: {}", label, e),
+				}
+			}));
+		}
+		// NeoForge 不能加载 OptiFine，Fabric 与 Quilt 需要另装 OptiFabric
+		if let (Some(loader), Some(game)) = (&inst.loader, inst.game_version.clone())
+			&& matches!(
+				loader.kind,
+				LoaderKind::Forge | LoaderKind::Fabric | LoaderKind::Quilt
+			) {
+			let inst = inst.clone();
+			actions = actions.child(ActionButton::render("添加 OptiFine 模组", move |_| {
+				let (inst, game_version) = (inst.clone(), game.clone());
+				TasksView::spawn(
+					format!("添加 OptiFine 到 {}", inst.version),
+					move |progress| InstallOptiFineTask {
+						cluster_path: inst.cluster_path.clone(),
+						game_version,
+						build: None,
+						target: OptiFineTarget::Mods(inst),
+						progress: Some(progress),
+					},
+				);
+			}));
+		}
+		// 已有实例原地切换加载器，旧的版本 JSON 留作回滚点
+		if inst
+			.game_version
+			.as_ref()
+			.is_some_and(|game| *game != inst.version)
+		{
+			let current = inst.loader.as_ref().map(|l| l.kind);
+			for kind in [
+				LoaderKind::Fabric,
+				LoaderKind::Quilt,
+				LoaderKind::Forge,
+				LoaderKind::NeoForge,
+			] {
+				if current == Some(kind) {
+					continue;
+				}
+				let inst = inst.clone();
+				actions = actions.child(ActionButton::render(
+					&format!("切换为 {kind}"),
+					move |_| Self::switch_loader(inst.clone(), Some(kind)),
+				));
+			}
+			if current.is_some() {
+				let inst = inst.clone();
+				actions = actions.child(ActionButton::render("移除加载器", move |_| {
+					Self::switch_loader(inst.clone(), None)
+				}));
+			}
+			let inst = inst.clone();
+			actions =
+				actions.child(ActionButton::render(
+					"回滚加载器",
+					move |_| match rollback_loader(&inst) {
+						Ok(loader) => {
+							AppState::get().scan_instances();
+							tracing::info!(
+								"{} 已回滚到 {}",
+								inst.version,
+								loader.map_or("原版".to_string(), |l| format!(
+									"{} {}",
+									l.kind, l.version
+								))
+							);
+						}
+						Err(e) => tracing::error!("回滚 {} 失败: {}", inst.version, e),
+					},
+				));
+		}
+
+		div()
+			.flex()
+			.flex_col()
+			.gap_3()
+			.p_4()
+			.rounded_lg()
+			.bg(rgb(0x141414))
+			.child(
+				div()
+					.flex()
+					.items_center()
+					.justify_between()
+					.child(
+						div()
+							.text_lg()
+							.text_color(rgb(0xffffff))
+							.child(format!("{} 的加载器", inst.version)),
+					)
+					.child(div().text_sm().text_color(rgb(0x888888)).child(current)),
+			)
+			.child(actions)
+	}
+
+	fn switch_loader(inst: GameInstance, loader: Option<LoaderKind>) {
+		let label = match loader {
+			Some(kind) => format!("切换 {} 为 {kind}", inst.version),
+			None => format!("移除 {} 的加载器", inst.version),
+		};
+		TasksView::spawn_with(
+			label,
+			move |progress| SwitchLoaderTask {
+				instance: inst,
+				loader,
+				loader_version: None,
+				progress: Some(progress),
+			},
+			|switch| {
+				let name = |l: &Option<LoaderInfo>| {
+					l.as_ref().map_or("原版".to_string(), |l| {
+						format!("{} {}", l.kind, l.version)
+					})
+				};
+				tracing::info!(
+					"{}：{} -> {}，{} 个库有变化，回滚点 {}",
+					switch.instance.version,
+					name(&switch.previous),
+					name(&switch.current),
+					switch.libraries.len(),
+					switch.rollback.display()
+				);
+				AppState::get().scan_instances();
+			},
+		);
+	}
+
+	// 如 `1.20.1 · Fabric 0.15.0 · Java 17`
+	fn summary(inst: &GameInstance) -> Option<String> {
+		let mut parts = vec![inst.game_version.clone()?];
+		if let Some(loader) = &inst.loader {
+			parts.push(format!("{} {}", loader.kind, loader.version));
+		}
+		if let Some(java) = inst.java_major {
+			parts.push(format!("Java {java}"));
+		}
+		Some(parts.join(" · "))
+	}
+
+	fn render_java(inst: GameInstance) -> impl IntoElement {
+		let config = ConfigManager::load_game_config(&inst.cluster_path, &inst.version);
+
+		div()
+			.flex()
+			.flex_col()
+			.gap_3()
+			.p_4()
+			.rounded_lg()
+			.bg(rgb(0x141414))
+			.child(
+				div()
+					.text_lg()
+					.text_color(rgb(0xffffff))
+					.child(format!("{} 的 Java", inst.version)),
+			)
+			.child(JavaPicker::render(config.java_path.clone(), move |path| {
+				let mut config = ConfigManager::load_game_config(&inst.cluster_path, &inst.version);
+				config.java_path = path;
+				if let Err(e) =
+					ConfigManager::save_game_config(&inst.cluster_path, &inst.version, &config)
+				{
+					tracing::error!("Failed to save instance config: {}", e);
+				}
+			}))
 	}
 }
@@ -1,10 +1,20 @@
 use crate::launcher::core::state::AppState;
-use gpui::{div, prelude::*, rgb};
+use crate::minecraft::account::offline::{OfflineUuid, OfflineUuidScheme};
+use crate::minecraft::account::skin::SkinModel;
+use crate::minecraft::account::{Account, AccountEntry, AccountId, SessionState};
+use crate::minecraft::tasks::account::{
+	LoginMicrosoftTask, SelectCapeTask, SwitchOfflineUuidTask, SyncSkinTask, UploadSkinTask,
+};
+use crate::ui::components::account_form::AccountForm;
+use crate::ui::components::action_button::ActionButton;
+use crate::ui::components::java_picker::JavaPicker;
+use crate::ui::views::tasks::TasksView;
+use gpui::{App, Entity, PathPromptOptions, div, img, prelude::*, rgb};
 
 pub struct SettingsView;
 
 impl SettingsView {
-	pub fn render() -> impl IntoElement {
+	pub fn render(account_form: Entity<AccountForm>) -> impl IntoElement {
 		let config = AppState::get().config.get();
 		let cluster_path = AppState::get().cluster_path();
 
@@ -15,6 +25,10 @@ impl SettingsView {
 			.p_4()
 			.gap_4()
 			.child(div().text_xl().text_color(rgb(0xffffff)).child("设置"))
+			.child(Self::render_section(
+				"账户",
+				Self::render_accounts().child(account_form),
+			))
 			.child(Self::render_section(
 				"启动器设置",
 				div()
@@ -43,17 +57,6 @@ impl SettingsView {
 					.flex()
 					.flex_col()
 					.gap_3()
-					.child(Self::render_setting_item(
-						"Java 路径",
-						config
-							.game
-							.java_path
-							.as_ref()
-							.map(|p| p.display().to_string())
-							.as_deref()
-							.unwrap_or("自动检测"),
-						"游戏使用的 Java 运行时",
-					))
 					.child(Self::render_setting_item(
 						"最大内存",
 						&format!("{} MB", config.game.max_memory_mb),
@@ -77,6 +80,29 @@ impl SettingsView {
 						"额外的 JVM 启动参数",
 					)),
 			))
+			.child(Self::render_section(
+				"Java 运行时",
+				div()
+					.flex()
+					.flex_col()
+					.gap_3()
+					.child(
+						div()
+							.flex()
+							.items_center()
+							.justify_between()
+							.child(
+								div()
+									.text_xs()
+									.text_color(rgb(0x666666))
+									.child("游戏默认使用的 Java，实例可单独覆盖"),
+							)
+							.child(JavaPicker::render_rescan()),
+					)
+					.child(JavaPicker::render(config.game.java_path.clone(), |path| {
+						let _ = AppState::get().config.update(|c| c.game.java_path = path);
+					})),
+			))
 			.child(Self::render_section(
 				"网络设置",
 				div()
@@ -91,6 +117,213 @@ impl SettingsView {
 			))
 	}
 
+	fn render_accounts() -> gpui::Div {
+		let accounts = AppState::get().accounts.list();
+		let current = AppState::get().accounts.current_id();
+
+		let mut list = div().flex().flex_col().gap_3();
+		if accounts.is_empty() {
+			list = list.child(Self::render_setting_item(
+				"未添加账户",
+				"离线 Player",
+				"启动时使用默认离线账户",
+			));
+		}
+		for entry in accounts {
+			let kind = match &entry.account {
+				Account::Offline { .. } => "离线账户",
+				Account::Microsoft { .. } => "微软账户",
+				Account::Yggdrasil { .. } => "外置登录账户",
+			};
+			let desc = if current == Some(entry.id) {
+				format!("{kind}（当前）")
+			} else {
+				kind.to_string()
+			};
+			let avatar = AppState::get().accounts.cached_avatar(entry.id);
+			list = list.child(
+				div()
+					.flex()
+					.items_center()
+					.gap_3()
+					.child(match avatar {
+						Some(path) => img(path).size_8().rounded_sm().into_any_element(),
+						None => div()
+							.size_8()
+							.rounded_sm()
+							.bg(rgb(0x252525))
+							.into_any_element(),
+					})
+					.child(div().flex_grow().child(Self::render_setting_item(
+						entry.account.username(),
+						Self::account_state(&entry.account),
+						&desc,
+					))),
+			);
+			list = list.child(Self::render_account_actions(
+				&entry,
+				current == Some(entry.id),
+			));
+			if let Account::Offline {
+				scheme: OfflineUuidScheme::Legacy,
+				..
+			} = &entry.account
+			{
+				list = list.child(Self::render_uuid_migration(entry.id));
+			}
+		}
+		list.child(
+			div()
+				.flex()
+				.gap_2()
+				.child(ActionButton::render("添加微软账户", |_| {
+					TasksView::spawn("微软账户登录".to_string(), |progress| {
+						LoginMicrosoftTask {
+							progress: Some(progress),
+						}
+					});
+				})),
+		)
+	}
+
+	fn render_account_actions(entry: &AccountEntry, is_current: bool) -> impl IntoElement {
+		let id = entry.id;
+		let name = entry.account.username().to_string();
+		let mut actions = div().flex().flex_wrap().gap_2();
+		if !is_current {
+			actions = actions.child(ActionButton::render("设为当前", move |_| {
+				AppState::get().accounts.select(Some(id))
+			}));
+		}
+		if !entry.account.is_offline() {
+			let label = format!("同步 {name} 的皮肤");
+			actions = actions.child(ActionButton::render("同步皮肤", move |_| {
+				TasksView::spawn(label.clone(), move |progress| SyncSkinTask {
+					account: id,
+					progress: Some(progress),
+				})
+			}));
+			for (model, label) in [
+				(SkinModel::Classic, "上传皮肤（经典）"),
+				(SkinModel::Slim, "上传皮肤（纤细）"),
+			] {
+				let name = name.clone();
+				actions = actions.child(ActionButton::render(label, move |cx| {
+					Self::upload_skin(id, name.clone(), model, cx)
+				}));
+			}
+		}
+		let appearance = AppState::get().accounts.cached_appearance(id);
+		if let Some(skin) = appearance.as_ref().and_then(|a| a.skin.as_ref()) {
+			actions = actions.child(div().text_xs().text_color(rgb(0x666666)).child(
+				match skin.model {
+					SkinModel::Classic => "当前皮肤：经典",
+					SkinModel::Slim => "当前皮肤：纤细",
+				},
+			));
+		}
+		// 披风列表来自上次同步的外观，只有正版账户可以切换
+		if let (Account::Microsoft { .. }, Some(appearance)) = (&entry.account, appearance) {
+			for cape in &appearance.capes {
+				let alias = cape.alias.clone().unwrap_or_else(|| cape.id.clone());
+				let label = if cape.active {
+					format!("披风 {alias}（使用中）")
+				} else {
+					format!("披风 {alias}")
+				};
+				let cape_id = cape.id.clone();
+				actions = actions.child(ActionButton::render(&label, move |_| {
+					Self::select_cape(id, Some(cape_id.clone()))
+				}));
+			}
+			if appearance.active_cape().is_some() {
+				actions = actions.child(ActionButton::render("隐藏披风", move |_| {
+					Self::select_cape(id, None)
+				}));
+			}
+		}
+		actions.child(ActionButton::render("删除", move |_| {
+			AppState::get().accounts.remove(id);
+			tracing::info!("已删除账户 {}", name);
+		}))
+	}
+
+	fn upload_skin(id: AccountId, name: String, model: SkinModel, cx: &mut App) {
+		let paths = cx.prompt_for_paths(PathPromptOptions {
+			files: true,
+			directories: false,
+			multiple: false,
+			prompt: Some("选择皮肤".into()),
+		});
+		tokio::runtime::Handle::current().spawn(async move {
+			let Ok(Ok(Some(paths))) = paths.await else {
+				return;
+			};
+			let Some(path) = paths.into_iter().next() else {
+				return;
+			};
+			TasksView::spawn(format!("上传 {name} 的皮肤"), move |progress| {
+				UploadSkinTask {
+					account: id,
+					path,
+					model,
+					progress: Some(progress),
+				}
+			});
+		});
+	}
+
+	fn select_cape(id: AccountId, cape: Option<String>) {
+		TasksView::spawn("切换披风".to_string(), move |progress| SelectCapeTask {
+			account: id,
+			cape,
+			progress: Some(progress),
+		});
+	}
+
+	// 旧版 UUID 与原版服务端不一致，切换时一并迁移存档中的玩家数据
+	fn render_uuid_migration(id: AccountId) -> impl IntoElement {
+		div()
+			.flex()
+			.items_center()
+			.justify_between()
+			.child(
+				div()
+					.text_xs()
+					.text_color(rgb(0x666666))
+					.child("该账户使用旧版 UUID，与服务器离线模式不一致"),
+			)
+			.child(ActionButton::render("改用原版 UUID", move |_| {
+				tokio::runtime::Handle::current().spawn(async move {
+					let task = SwitchOfflineUuidTask {
+						account: id,
+						uuid: OfflineUuid::Vanilla,
+					};
+					let result = match AppState::get().task_manager.submit_concurrent(task).await {
+						Ok(mut h) => h.result().await,
+						Err(e) => Err(e),
+					};
+					match result {
+						Ok(report) => {
+							tracing::info!("已迁移 {} 个玩家数据文件", report.moved.len())
+						}
+						Err(e) => tracing::error!("迁移 UUID 失败: {}", e),
+					}
+				});
+			}))
+	}
+
+	fn account_state(account: &Account) -> &'static str {
+		match account.session_state() {
+			SessionState::Revoked => "需要重新登录",
+			SessionState::Expired => "登录已过期",
+			SessionState::Valid if account.is_offline() => "离线",
+			SessionState::Valid if account.is_demo() => "未购买游戏（试玩）",
+			SessionState::Valid if matches!(account, Account::Microsoft { .. }) => "已拥有游戏",
+			SessionState::Valid => "已登录",
+		}
+	}
+
 	fn render_section(title: &str, content: impl IntoElement) -> impl IntoElement {
 		div()
 			.flex()
@@ -142,3 +375,26 @@ impl SettingsView {
 			)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use uuid::Uuid;
+
+	#[test]
+	fn test_expired_owner_state() {
+		let account = Account::Microsoft {
+			username: "Alex".into(),
+			uuid: Uuid::new_v4(),
+			access_token: "mc".into(),
+			refresh_token: "rt".into(),
+			client_id: "client".into(),
+			xuid: String::new(),
+			expires_at: 1,
+			revoked: false,
+			owns_game: true,
+		};
+		assert_eq!(account.session_state(), SessionState::Expired);
+		assert_eq!(SettingsView::account_state(&account), "登录已过期");
+	}
+}
@@ -1,4 +1,5 @@
 use crate::launcher::core::state::AppState;
+use crate::launcher::task::framework::ConcurrentTask;
 use crate::minecraft::tasks::download::{DownloadProgressState, ProgressRef};
 use crate::launcher::task::handle::TaskId;
 use gpui::{div, prelude::*, px, rgb};
@@ -57,6 +58,44 @@ impl TasksView {
 			})
 	}
 
+	/// 提交任务并登记进度，`label` 用于日志中的完成与失败提示
+	pub fn spawn<T: ConcurrentTask>(
+		label: String,
+		task: impl FnOnce(ProgressRef) -> T + Send + 'static,
+	) {
+		Self::spawn_with(label, task, |_| {});
+	}
+
+	/// 同 [`Self::spawn`]，任务成功后把结果交给 `on_done`
+	pub fn spawn_with<T: ConcurrentTask>(
+		label: String,
+		task: impl FnOnce(ProgressRef) -> T + Send + 'static,
+		on_done: impl FnOnce(T::Output) + Send + 'static,
+	) {
+		tokio::runtime::Handle::current().spawn(async move {
+			let state = AppState::get();
+			let progress = ProgressRef::default();
+			let result = match state
+				.task_manager
+				.submit_concurrent(task(progress.clone()))
+				.await
+			{
+				Ok(mut h) => {
+					state.task_progress.lock().unwrap().insert(h.id, progress);
+					h.result().await
+				}
+				Err(e) => Err(e),
+			};
+			match result {
+				Ok(output) => {
+					tracing::info!("{}完成", label);
+					on_done(output);
+				}
+				Err(e) => tracing::error!("{}失败: {}", label, e),
+			}
+		});
+	}
+
 	fn render_task_item(task_id: TaskId, progress: ProgressRef) -> impl IntoElement {
 		let p = {
 			let rt = tokio::runtime::Handle::current();